@@ -6,3 +6,40 @@ pub use failure::{Error, Fail};
 pub struct InvalidArguments {
     pub num_args: usize,
 }
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Invalid input value: '{}' ({})", value, reason)]
+pub struct InvalidInputValue {
+    pub value: String,
+    // why the value didn't parse
+    pub reason: String,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum InvalidOption {
+    #[fail(display = "Unknown option: --{}", name)]
+    Unknown {
+        name: String,
+    },
+
+    #[fail(display = "Missing value for option: --{}", name)]
+    MissingValue {
+        name: String,
+    },
+
+    #[fail(display = "Unexpected value for flag --{}: '{}'", name, value)]
+    UnexpectedValue {
+        name: String,
+        value: String,
+    },
+
+    #[fail(display = "Invalid value for option --{}: '{}' ({})", name, value, reason)]
+    InvalidValue {
+        name: String,
+        value: String,
+        // why the value didn't parse
+        reason: String,
+    },
+}
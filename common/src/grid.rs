@@ -0,0 +1,456 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+use std::str::FromStr;
+
+use crate::errors::Fail;
+
+
+/// Two dimensional grid of cells, indexed by `(x, y)` with (0, 0) the
+/// top left cell.  Cells are stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ParseGridError {
+    #[fail(display = "Row {} is {} cells wide, expected {}", row, width, expected)]
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+
+    #[fail(display = "Invalid cell '{}' at {},{}", ch, x, y)]
+    InvalidCell {
+        ch: char,
+        x: usize,
+        y: usize,
+    },
+}
+
+
+// offsets of the neighbours sharing an edge, then the diagonal ones
+const NEIGHBOUR_OFFSETS: [(isize, isize); 8] = [
+    (0, -1), (1, 0), (0, 1), (-1, 0),
+    (1, -1), (1, 1), (-1, 1), (-1, -1),
+];
+
+
+impl<T> Grid<T> {
+    /// Grid with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T>
+        where T: Clone
+    {
+        Grid { width, height, cells: vec![value; width * height] }
+    }
+
+    /// Grid with each cell set to `cell(x, y)`.
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(usize, usize) -> T) -> Grid<T> {
+        let mut cells = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(cell(x, y));
+            }
+        }
+
+        Grid { width, height, cells }
+    }
+
+    /// Grid of characters, one row per line, each converted with
+    /// `parse` (`None` meaning the character is invalid).  Trailing
+    /// empty lines are ignored.
+    pub fn parse_with(text: &str, parse: impl Fn(char) -> Option<T>) -> Result<Grid<T>, ParseGridError> {
+        let lines: Vec<&str> = text.trim_end_matches('\n').lines().collect();
+
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+
+        for (y, line) in lines.iter().enumerate() {
+            let row_width = line.chars().count();
+
+            if row_width != width {
+                return Err(ParseGridError::RaggedRow { row: y, width: row_width, expected: width });
+            }
+
+            for (x, ch) in line.chars().enumerate() {
+                cells.push(parse(ch).ok_or(ParseGridError::InvalidCell { ch, x, y })?);
+            }
+        }
+
+        // no rows without any cells
+        let height = if width > 0 { lines.len() } else { 0 };
+
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Width and height.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// All the cells, row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// Positions of all the cells, row by row.
+    pub fn positions(&self) -> impl Iterator<Item=(usize, usize)> {
+        let width = self.width;
+
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Cells with their positions, row by row.
+    pub fn iter_positions(&self) -> impl Iterator<Item=((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        // chunks of 0 cells aren't allowed
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        assert!(x < self.width, "column {} out of a grid {} wide", x, self.width);
+
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Cells of the given columns and rows, row by row.  Panics if
+    /// the region isn't within the grid, like slicing.
+    pub fn region(&self, columns: Range<usize>, rows: Range<usize>) -> impl Iterator<Item=&T> {
+        let width = self.width;
+
+        self.cells[rows.start * width..rows.end * width]
+            .chunks(width.max(1))
+            .flat_map(move |row| row[columns.clone()].iter())
+    }
+
+    pub fn region_mut(&mut self, columns: Range<usize>, rows: Range<usize>) -> impl Iterator<Item=&mut T> {
+        let width = self.width;
+
+        self.cells[rows.start * width..rows.end * width]
+            .chunks_mut(width.max(1))
+            .flat_map(move |row| row[columns.clone()].iter_mut())
+    }
+
+    /// Positions of the (up to 4) cells sharing an edge with the cell.
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize)> + '_ {
+        self.neighbours(x, y, &NEIGHBOUR_OFFSETS[..4])
+    }
+
+    /// Positions of the (up to 8) cells sharing an edge or a corner
+    /// with the cell.
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item=(usize, usize)> + '_ {
+        self.neighbours(x, y, &NEIGHBOUR_OFFSETS)
+    }
+
+    fn neighbours(&self, x: usize, y: usize, offsets: &'static [(isize, isize)])
+                  -> impl Iterator<Item=(usize, usize)> + '_ {
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| {
+                let position = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+
+                Some(position).filter(|&(x, y)| self.contains(x, y))
+            })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Grid mirrored along its main diagonal, rows becoming columns.
+    pub fn transpose(&self) -> Grid<T>
+        where T: Clone
+    {
+        Grid::from_fn(self.height, self.width, |x, y| self[(y, x)].clone())
+    }
+
+    /// Grid turned a quarter clockwise.
+    pub fn rotate_clockwise(&self) -> Grid<T>
+        where T: Clone
+    {
+        let height = self.height;
+
+        Grid::from_fn(self.height, self.width, |x, y| self[(y, height - 1 - x)].clone())
+    }
+
+    /// Grid turned a quarter anticlockwise.
+    pub fn rotate_anticlockwise(&self) -> Grid<T>
+        where T: Clone
+    {
+        let width = self.width;
+
+        Grid::from_fn(self.height, self.width, |x, y| self[(width - 1 - y, x)].clone())
+    }
+
+    /// Positions of the cells reachable from `start` through cells
+    /// sharing an edge, all of them matching `include`, in breadth
+    /// first order.  Empty if `start` itself doesn't match.
+    pub fn connected(&self, start: (usize, usize), include: impl Fn(&T) -> bool) -> Vec<(usize, usize)> {
+        let mut seen = vec![false; self.cells.len()];
+        let mut queue = VecDeque::new();
+        let mut connected = vec![];
+
+        if self.get(start.0, start.1).is_some_and(&include) {
+            seen[start.1 * self.width + start.0] = true;
+            queue.push_back(start);
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            connected.push((x, y));
+
+            for (x, y) in self.neighbours4(x, y) {
+                let index = y * self.width + x;
+
+                if !seen[index] && include(&self.cells[index]) {
+                    seen[index] = true;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        connected
+    }
+
+    /// Set the cells connected to `start` and equal to it to `value`,
+    /// returning the number of cells filled.
+    pub fn flood_fill(&mut self, start: (usize, usize), value: T) -> usize
+        where T: Clone + PartialEq
+    {
+        let target = match self.get(start.0, start.1) {
+            Some(target) => target.clone(),
+            None => return 0,
+        };
+
+        let area = self.connected(start, |cell| *cell == target);
+
+        for &(x, y) in &area {
+            self[(x, y)] = value.clone();
+        }
+
+        area.len()
+    }
+}
+
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("cell {},{} out of a {}x{} grid", x, y, self.width, self.height))
+    }
+}
+
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = self.size();
+
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("cell {},{} out of a {}x{} grid", x, y, width, height))
+    }
+}
+
+
+impl FromStr for Grid<char> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Grid<char>, ParseGridError> {
+        Grid::parse_with(s, Some)
+    }
+}
+
+
+/// One line per row, cells written next to each other.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+
+    const MAP: &str = "#..\n.#.\n...\n##.\n";
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid: Grid<char> = MAP.parse().unwrap();
+
+        assert_eq!(grid.size(), (3, 4));
+        assert_eq!(grid[(1, 1)], '#');
+        assert_eq!(grid.get(0, 3), Some(&'#'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.to_string(), MAP);
+
+        let grid = Grid::parse_with("19\n28", |ch| ch.to_digit(10)).unwrap();
+        assert_eq!(grid.cells(), &[1, 9, 2, 8]);
+
+        let err = Grid::parse_with("19\n2x", |ch| ch.to_digit(10)).unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid cell 'x' at 1,1");
+
+        let err = "#..\n.#\n".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(format!("{}", err), "Row 1 is 2 cells wide, expected 3");
+
+        let empty: Grid<char> = "".parse().unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.size(), (0, 0));
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut grid = Grid::filled(2, 2, 0);
+
+        *grid.get_mut(1, 0).unwrap() = 5;
+        grid[(0, 1)] += 2;
+
+        assert!(grid.get_mut(2, 0).is_none());
+        assert_eq!(grid.cells(), &[0, 5, 2, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cell 2,0 out of a 2x2 grid")]
+    fn test_index_out_of_bounds() {
+        let grid = Grid::filled(2, 2, 0);
+        let _ = grid[(2, 0)];
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::filled(3, 3, ());
+
+        assert_eq!(grid.neighbours4(1, 1).collect::<Vec<_>>(), vec![(1, 0), (2, 1), (1, 2), (0, 1)]);
+        assert_eq!(grid.neighbours4(0, 0).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+
+        assert_eq!(grid.neighbours8(1, 1).count(), 8);
+        assert_eq!(grid.neighbours8(2, 0).collect::<Vec<_>>(), vec![(2, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = Grid::from_fn(4, 3, |x, y| x + 10 * y);
+
+        assert_eq!(grid.row(1), &[10, 11, 12, 13]);
+        assert_eq!(grid.column(2).cloned().collect::<Vec<_>>(), vec![2, 12, 22]);
+        assert_eq!(grid.region(1..3, 1..3).cloned().collect::<Vec<_>>(), vec![11, 12, 21, 22]);
+        assert_eq!(grid.region(1..1, 0..3).count(), 0);
+        assert_eq!(grid.rows().count(), 3);
+
+        let mut grid = grid;
+
+        for cell in grid.region_mut(0..2, 2..3) {
+            *cell = 0;
+        }
+
+        grid.row_mut(0)[3] = 99;
+
+        assert_eq!(grid.cells(), &[0, 1, 2, 99, 10, 11, 12, 13, 0, 0, 22, 23]);
+
+        assert_eq!(
+            grid.iter_positions().filter(|(_, &cell)| cell == 0).map(|(position, _)| position).collect::<Vec<_>>(),
+            vec![(0, 0), (0, 2), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let grid: Grid<char> = "ab\ncd\nef\n".parse().unwrap();
+
+        assert_eq!(grid.transpose().to_string(), "ace\nbdf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "eca\nfdb\n");
+        assert_eq!(grid.rotate_anticlockwise().to_string(), "bdf\nace\n");
+
+        assert_eq!(grid.rotate_clockwise().rotate_anticlockwise(), grid);
+        assert_eq!(grid.map(|ch| ch.to_ascii_uppercase()).to_string(), "AB\nCD\nEF\n");
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut grid: Grid<char> = MAP.parse().unwrap();
+
+        assert_eq!(
+            grid.connected((1, 0), |&ch| ch == '.'),
+            vec![(1, 0), (2, 0), (2, 1), (2, 2), (2, 3), (1, 2), (0, 2), (0, 1)]
+        );
+        assert!(grid.connected((0, 0), |&ch| ch == '.').is_empty());
+
+        assert_eq!(grid.flood_fill((2, 2), 'o'), 8);
+        assert_eq!(grid.to_string(), "#oo\no#o\nooo\n##o\n");
+
+        // diagonal cells aren't connected
+        assert_eq!(grid.flood_fill((0, 0), '~'), 1);
+        assert_eq!(grid.flood_fill((5, 5), '~'), 0);
+    }
+}
@@ -1,12 +1,16 @@
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use std::fmt::Display;
-use std::fs::File;
+use std::fs::{self, File};
 use std::path::Path;
 use std::str::FromStr;
 
 mod errors;
+pub mod grid;
+mod options;
+pub mod summed_area;
 
-pub use crate::errors::{Error, Fail, InvalidArguments};
+pub use crate::errors::{Error, Fail, InvalidArguments, InvalidInputValue, InvalidOption};
+pub use crate::options::{parse_options, Options};
 
 
 pub fn get_input<T, U>(args: &[U]) -> Result<Vec<T>, Error>
@@ -14,23 +18,81 @@ pub fn get_input<T, U>(args: &[U]) -> Result<Vec<T>, Error>
           <T as FromStr>::Err: Fail,
           U: AsRef<str> + AsRef<Path> + Display
 {
-    let line_inputs;
+    get_input_with(args, str::parse::<T>)
+}
+
+
+/// Like `get_input`, but parsing each line with the given function.
+pub fn get_input_with<T, E, U, F>(args: &[U], parse: F) -> Result<Vec<T>, Error>
+    where F: Fn(&str) -> Result<T, E>,
+          E: Fail,
+          U: AsRef<str> + AsRef<Path> + Display
+{
+    let line_inputs = get_input_lines(args)?;
+
+    parse_lines_with(&line_inputs, parse)
+}
+
+
+/// Whole input as a single string, for inputs which aren't line based.
+pub fn get_input_text<U>(args: &[U]) -> Result<String, Error>
+    where U: AsRef<str> + AsRef<Path> + Display
+{
+    match get_input_path(args)? {
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+
+            Ok(text)
+        },
+        Some(path) => Ok(fs::read_to_string(path)?),
+    }
+}
+
 
+/// Whole input as a single value, e.g. one number, ignoring any
+/// whitespace around it.
+pub fn get_input_value<T, U>(args: &[U]) -> Result<T, Error>
+    where T: FromStr,
+          <T as FromStr>::Err: Fail,
+          U: AsRef<str> + AsRef<Path> + Display
+{
+    let text = get_input_text(args)?;
+    let value = text.trim();
+
+    value
+        .parse::<T>()
+        .map_err(|err| InvalidInputValue { value: value.into(), reason: err.to_string() }.into())
+}
+
+
+fn get_input_lines<U>(args: &[U]) -> Result<Vec<String>, Error>
+    where U: AsRef<str> + AsRef<Path> + Display
+{
+    match get_input_path(args)? {
+        None => get_stdin_input(),
+        Some(path) => get_file_input(path),
+    }
+}
+
+
+// input file path, `None` meaning stdin
+fn get_input_path<U>(args: &[U]) -> Result<Option<&U>, Error>
+    where U: AsRef<str> + AsRef<Path> + Display
+{
     match args.len() - 1 {
         0 => {
             eprintln!("Reading input from stdin.");
-            line_inputs = get_stdin_input()?;
+            Ok(None)
         },
         arg_idx @ 1 => {
             eprintln!("Reading input from file: `{}'.", args[arg_idx]);
-            line_inputs = get_file_input(&args[arg_idx])?;
+            Ok(Some(&args[arg_idx]))
         },
         num_args => {
-            return Err(InvalidArguments { num_args }.into())
+            Err(InvalidArguments { num_args }.into())
         }
-    };
-
-    parse_lines::<T, _>(&line_inputs)
+    }
 }
 
 
@@ -66,15 +128,25 @@ fn read_lines(reader: impl BufRead) -> Result<Vec<String>, Error> {
 }
 
 
+#[cfg(test)]
 fn parse_lines<T, U>(lines: &[U]) -> Result<Vec<T>, Error>
     where T: FromStr,
           <T as FromStr>::Err: Fail,
           U: AsRef<str>
+{
+    parse_lines_with(lines, str::parse::<T>)
+}
+
+
+fn parse_lines_with<T, E, U, F>(lines: &[U], parse: F) -> Result<Vec<T>, Error>
+    where F: Fn(&str) -> Result<T, E>,
+          E: Fail,
+          U: AsRef<str>
 {
     let mut parsed: Vec<T> = vec![];
 
     for line in lines {
-        let number = line.as_ref().parse()?;
+        let number = parse(line.as_ref())?;
         parsed.push(number);
     }
 
@@ -87,8 +159,6 @@ mod tests {
     use super::*;
     use std::io::Write;
 
-    use tempfile;
-
     #[test]
     fn test_get_args_file() {
         let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
@@ -101,6 +171,45 @@ mod tests {
         assert_eq!(get_input::<i64, _>(&args).unwrap(), vec![1, 2, -33]);
     }
 
+    #[test]
+    fn test_get_input_with() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(b"1\n22\n333").unwrap();
+
+        let args = ["prog", tmp_file.path().to_str().unwrap()];
+        let parsed = get_input_with(&args, |line| line.parse::<i64>().map(|n| n * 2));
+
+        assert_eq!(parsed.unwrap(), vec![2, 44, 666]);
+    }
+
+    #[test]
+    fn test_get_input_text() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(b"[1,\n 2]\n").unwrap();
+
+        let args = ["prog", tmp_file.path().to_str().unwrap()];
+
+        assert_eq!(get_input_text(&args).unwrap(), "[1,\n 2]\n");
+
+        let args = ["prog", "one", "two"];
+        let err = get_input_text(&args).unwrap_err();
+
+        assert_eq!(format!("{}", err), get_input::<i64, _>(&args).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_get_input_value() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(b"  -7439\n").unwrap();
+
+        let args = ["prog", tmp_file.path().to_str().unwrap()];
+
+        assert_eq!(get_input_value::<i32, _>(&args).unwrap(), -7439);
+
+        let err = get_input_value::<u8, _>(&args).unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid input value: '-7439' (invalid digit found in string)");
+    }
+
     #[test]
     fn test_parse_lines_all_ok() {
         let input = ["1", "+16", "-42"];
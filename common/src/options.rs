@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::errors::{Error, Fail, InvalidOption};
+
+
+/// Command line options, given as `--name value`, `--name=value`
+/// or, for flags, just `--name`.
+#[derive(Debug, Default)]
+pub struct Options {
+    values: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+
+impl Options {
+    /// Parsed value of the option, `None` if it wasn't given.
+    pub fn value<T>(&self, name: &str) -> Result<Option<T>, Error>
+        where T: FromStr,
+              <T as FromStr>::Err: Fail
+    {
+        match self.values.get(name) {
+            None => Ok(None),
+            Some(value) => {
+                value
+                    .parse::<T>()
+                    .map(Some)
+                    .map_err(|err| InvalidOption::InvalidValue {
+                        name: name.into(), value: value.clone(), reason: err.to_string()
+                    }.into())
+            }
+        }
+    }
+
+    /// Parsed value of the option, or `default` if it wasn't given.
+    pub fn value_or<T>(&self, name: &str, default: T) -> Result<T, Error>
+        where T: FromStr,
+              <T as FromStr>::Err: Fail
+    {
+        self.value(name).map(|value| value.unwrap_or(default))
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+
+/// Split options out of the command line arguments, returning the
+/// remaining (positional) arguments, including the program name.
+///
+/// `value_names` are options taking a value, `flag_names` are options
+/// without one.  Anything else starting with `--` is an error.
+pub fn parse_options<U>(args: &[U],
+                        value_names: &[&str],
+                        flag_names: &[&str]) -> Result<(Vec<String>, Options), Error>
+    where U: AsRef<str>
+{
+    let mut positional = vec![];
+    let mut options = Options::default();
+
+    let mut args = args.iter().map(|arg| arg.as_ref());
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            positional.push(arg.to_string());
+            continue;
+        }
+
+        let (name, inline_value) = match arg[2..].find('=') {
+            Some(pos) => (&arg[2..2 + pos], Some(&arg[3 + pos..])),
+            None => (&arg[2..], None),
+        };
+
+        if flag_names.contains(&name) {
+            if let Some(value) = inline_value {
+                return Err(InvalidOption::UnexpectedValue {
+                    name: name.into(), value: value.into()
+                }.into());
+            }
+
+            options.flags.insert(name.into());
+        } else if value_names.contains(&name) {
+            let value = inline_value
+                .or_else(|| args.next())
+                .ok_or_else(|| InvalidOption::MissingValue { name: name.into() })?;
+
+            options.values.insert(name.into(), value.into());
+        } else {
+            return Err(InvalidOption::Unknown { name: name.into() }.into());
+        }
+    }
+
+    Ok((positional, options))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::parse_options;
+
+    #[test]
+    fn test_parse_options() {
+        let args = ["prog", "--size", "10", "input", "--verbose", "--mode=fast"];
+
+        let (positional, options) = parse_options(
+            &args, &["size", "mode", "unused"], &["verbose", "quiet"]
+        ).unwrap();
+
+        assert_eq!(positional, vec!["prog", "input"]);
+
+        assert_eq!(options.value::<u32>("size").unwrap(), Some(10));
+        assert_eq!(options.value::<String>("mode").unwrap(), Some("fast".into()));
+        assert_eq!(options.value::<u32>("unused").unwrap(), None);
+        assert_eq!(options.value_or::<u32>("unused", 7).unwrap(), 7);
+
+        assert!(options.flag("verbose"));
+        assert!(!options.flag("quiet"));
+    }
+
+    #[test]
+    fn test_parse_options_invalid_value() {
+        let args = ["prog", "--size", "big"];
+
+        let (_, options) = parse_options(&args, &["size"], &[]).unwrap();
+        let err = options.value::<u32>("size").unwrap_err();
+
+        assert_eq!(format!("{}", err), "Invalid value for option --size: 'big' (invalid digit found in string)");
+    }
+
+    #[test]
+    fn test_parse_options_errors() {
+        let error_message = |args: &[&str]| {
+            format!("{}", parse_options(args, &["size"], &["verbose"]).unwrap_err())
+        };
+
+        assert_eq!(error_message(&["prog", "--colour"]), "Unknown option: --colour");
+        assert_eq!(error_message(&["prog", "--size"]), "Missing value for option: --size");
+        assert_eq!(
+            error_message(&["prog", "--verbose=yes"]),
+            "Unexpected value for flag --verbose: 'yes'"
+        );
+    }
+}
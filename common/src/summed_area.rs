@@ -0,0 +1,99 @@
+use std::ops::{Add, Sub};
+
+
+/// Prefix sums of a per-cell value over a grid, for O(1) sums over
+/// any rect of cells.
+#[derive(Debug, Clone)]
+pub struct SummedArea<T> {
+    width: usize,
+    height: usize,
+    sums: Vec<T>,
+}
+
+
+impl<T> SummedArea<T>
+    where T: Copy + Default + Add<Output = T> + Sub<Output = T>
+{
+    pub fn new(width: usize, height: usize, value: impl Fn(usize, usize) -> T) -> SummedArea<T> {
+        // one extra row and column of zeros, to avoid edge cases
+        let sums = vec![T::default(); (width + 1) * (height + 1)];
+
+        let mut summed_area = SummedArea { width, height, sums };
+        summed_area.refill(value);
+
+        summed_area
+    }
+
+    /// Recomputes the sums from new values, reusing the table.
+    pub fn refill(&mut self, value: impl Fn(usize, usize) -> T) {
+        let stride = self.width + 1;
+        let sums = &mut self.sums;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // adding before subtracting keeps unsigned sums from
+                // going below zero
+                sums[(y + 1) * stride + x + 1] = value(x, y)
+                    + sums[y * stride + x + 1]
+                    + sums[(y + 1) * stride + x]
+                    - sums[y * stride + x];
+            }
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sum of the values of `width` by `height` cells from (x, y).
+    pub fn sum(&self, x: usize, y: usize, width: usize, height: usize) -> T {
+        let stride = self.width + 1;
+        let at = |x: usize, y: usize| self.sums[y * stride + x];
+
+        at(x + width, y + height) + at(x, y) - at(x, y + height) - at(x + width, y)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::SummedArea;
+
+    #[test]
+    fn test_sum() {
+        // 4x3 grid of x + 10 * y
+        let sums = SummedArea::new(4, 3, |x, y| x + 10 * y);
+
+        assert_eq!(sums.sum(0, 0, 4, 3), 6 * 3 + 10 * 4 * 3);
+        assert_eq!(sums.sum(1, 1, 2, 2), (11 + 12) + (21 + 22));
+        assert_eq!(sums.sum(3, 2, 1, 1), 23);
+        assert_eq!(sums.sum(2, 1, 0, 2), 0);
+
+        let empty = SummedArea::new(0, 0, |_, _| 1);
+        assert_eq!(empty.sum(0, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_negative_values() {
+        // 3x3 grid of alternating 1 and -2
+        let sums = SummedArea::new(3, 3, |x, y| if (x + y) % 2 == 0 { 1_i64 } else { -2 });
+
+        assert_eq!((sums.width(), sums.height()), (3, 3));
+        assert_eq!(sums.sum(0, 0, 3, 3), 5 - 8);
+        assert_eq!(sums.sum(1, 0, 1, 3), -2 + 1 - 2);
+        assert_eq!(sums.sum(0, 0, 2, 2), 1 - 2 - 2 + 1);
+    }
+
+    #[test]
+    fn test_refill() {
+        let mut sums = SummedArea::new(3, 2, |_, _| 1);
+        sums.refill(|x, _| x);
+
+        assert_eq!(sums.sum(0, 0, 3, 2), 2 * (1 + 2));
+        assert_eq!(sums.sum(2, 1, 1, 1), 2);
+    }
+}
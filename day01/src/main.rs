@@ -14,8 +14,8 @@ fn main() -> Result<(), Error> {
     let args: Vec<String> = env::args().collect();
     let input = get_input(&args);
 
-    if input.is_err() {
-        eprintln!("Error: {}. Aborting.", input.unwrap_err());
+    if let Err(err) = input {
+        eprintln!("Error: {}. Aborting.", err);
         std::process::exit(1);
     }
 
@@ -51,7 +51,7 @@ fn find_first_cycled_sum_repeat<T>(values: &[T], max_cycles: usize) -> Option<T>
 
     let max_iterations = (max_cycles + 1) * values.len();
 
-    let running_cycled_sum = values.iter()
+    let mut running_cycled_sum = values.iter()
         .cycle()
         .enumerate()
         // starting with default value for T, produce a running sum
@@ -69,11 +69,10 @@ fn find_first_cycled_sum_repeat<T>(values: &[T], max_cycles: usize) -> Option<T>
     seen.insert(T::default());
 
     running_cycled_sum
-        .skip_while(|sum| {
-            // insert will return true on duplicate
-            seen.insert(*sum)
+        .find(|sum| {
+            // insert will return false on duplicate
+            !seen.insert(*sum)
         })
-        .next()
 }
 
 
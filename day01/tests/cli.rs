@@ -4,7 +4,6 @@ mod cli {
     use std::process::Command;
 
     use assert_cmd::prelude::*;
-    use tempfile;
 
     #[test]
     fn test_run_stdin() {
@@ -35,7 +34,7 @@ mod cli {
         let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
         tmp_file.write_all(include_str!("../input/input").as_bytes()).unwrap();
 
-        cmd.arg(&tmp_file.path());
+        cmd.arg(tmp_file.path());
 
         cmd
             .assert()
@@ -9,8 +9,8 @@ fn main() -> Result<(), Error> {
     let args: Vec<String> = env::args().collect();
     let input = get_input(&args);
 
-    if input.is_err() {
-        eprintln!("Error: {}. Aborting.", input.unwrap_err());
+    if let Err(err) = input {
+        eprintln!("Error: {}. Aborting.", err);
         std::process::exit(1);
     }
 
@@ -24,7 +24,7 @@ fn main() -> Result<(), Error> {
 
     match common_string_parts(&strings, 1) {
         ref common if common.len() == 1 => println!("Common part: {}", common[0].common),
-        ref common if common.len() == 0 => {
+        ref common if common.is_empty() => {
             println!("No common parts with one difference!");
             std::process::exit(2);
         }
@@ -91,7 +91,7 @@ struct CommonString<'a> {
 }
 
 
-fn common_string_parts<T>(strings: &[T], differences: usize) -> Vec<CommonString>
+fn common_string_parts<T>(strings: &[T], differences: usize) -> Vec<CommonString<'_>>
     where T: AsRef<str>
 {
     let mut result = vec![];
@@ -158,7 +158,7 @@ mod tests {
     fn test_count_items() {
         let mut expected_counts = HashMap::new();
 
-        expected_counts.insert(1, 2 as usize);
+        expected_counts.insert(1, 2_usize);
         expected_counts.insert(2, 1);
         expected_counts.insert(3, 1);
         expected_counts.insert(5, 4);
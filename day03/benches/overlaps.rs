@@ -0,0 +1,88 @@
+// filling claim by claim, with a difference array and in parallel
+// (with the parallel feature)
+//
+//     cargo bench -p day03 --features parallel
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day03::overlaps::{FabricSize, Overlaps};
+use day03::rect::{ParseMode, Rect};
+
+
+// the puzzle input: ~1300 claims on a 1000x1000 fabric
+fn puzzle_rects() -> Vec<Rect> {
+    include_str!("../input/input")
+        .lines()
+        .map(|line| Rect::parse(line, ParseMode::Lenient).unwrap())
+        .collect()
+}
+
+
+// many more claims, on a larger fabric
+fn large_rects() -> Vec<Rect> {
+    let mut seed: u32 = 1;
+
+    let mut next = |limit: u32| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 8) % limit
+    };
+
+    (1..=200_000)
+        .map(|id| Rect { id, x: next(3970), y: next(3970), width: next(30) + 1, height: next(30) + 1 })
+        .collect()
+}
+
+
+fn bench_overlaps(c: &mut Criterion) {
+    let fabric = FabricSize { width: 4000, height: 4000 };
+
+    for (name, rects) in &[("puzzle", puzzle_rects()), ("large", large_rects())] {
+        let mut group = c.benchmark_group(*name);
+        group.sample_size(10);
+
+        group.bench_function("new", |b| {
+            b.iter(|| Overlaps::<u32>::new(black_box(rects)).unwrap())
+        });
+
+        group.bench_function("with_fabric", |b| {
+            b.iter(|| Overlaps::<u32>::with_fabric(black_box(rects), fabric).unwrap())
+        });
+
+        group.bench_function("diff_new", |b| {
+            b.iter(|| Overlaps::<u32>::diff_new(black_box(rects)).unwrap())
+        });
+
+        group.bench_function("diff_with_fabric", |b| {
+            b.iter(|| Overlaps::<u32>::diff_with_fabric(black_box(rects), fabric).unwrap())
+        });
+
+        let overlaps = Overlaps::<u32>::new(rects).unwrap();
+
+        group.bench_function("non_overlapping_rects", |b| {
+            b.iter(|| overlaps.iter_non_overlapping_rects().count())
+        });
+
+        #[cfg(feature = "parallel")]
+        {
+            group.bench_function("par_new", |b| {
+                b.iter(|| Overlaps::<u32>::par_new(black_box(rects)).unwrap())
+            });
+
+            group.bench_function("par_with_fabric", |b| {
+                b.iter(|| Overlaps::<u32>::par_with_fabric(black_box(rects), fabric).unwrap())
+            });
+
+            group.bench_function("par_non_overlapping_rects", |b| {
+                b.iter(|| overlaps.par_non_overlapping_rects().len())
+            });
+        }
+
+        group.finish();
+    }
+}
+
+
+criterion_group!(benches, bench_overlaps);
+criterion_main!(benches);
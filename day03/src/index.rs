@@ -0,0 +1,500 @@
+use std::cmp::{max, min, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::rect::{wide, ClaimID, Coordinate, Rect, RectIDType};
+
+
+// maximum number of children of a single tree node
+const NODE_CAPACITY: usize = 8;
+
+
+// inclusive cell bounds, following the `Rect::top_right` convention
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds<C> {
+    min_x: C,
+    min_y: C,
+    max_x: C,
+    max_y: C,
+}
+
+
+impl<C: Coordinate> Bounds<C> {
+    fn of_rect<I: ClaimID>(rect: &Rect<C, I>) -> Bounds<C> {
+        let (max_x, max_y) = rect.clamped_top_right();
+
+        Bounds { min_x: rect.x, min_y: rect.y, max_x, max_y }
+    }
+
+    fn point(x: C, y: C) -> Bounds<C> {
+        Bounds { min_x: x, min_y: y, max_x: x, max_y: y }
+    }
+
+    fn union(&self, other: &Bounds<C>) -> Bounds<C> {
+        Bounds {
+            min_x: min(self.min_x, other.min_x),
+            min_y: min(self.min_y, other.min_y),
+            max_x: max(self.max_x, other.max_x),
+            max_y: max(self.max_y, other.max_y),
+        }
+    }
+
+    fn intersects(&self, other: &Bounds<C>) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x &&
+            self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+
+    // doubled centre coordinates, to stay in integers
+    fn center_x(&self) -> i128 {
+        wide(self.min_x).saturating_add(wide(self.max_x))
+    }
+
+    fn center_y(&self) -> i128 {
+        wide(self.min_y).saturating_add(wide(self.max_y))
+    }
+
+    // squared euclidean distance from a cell to the closest cell within bounds
+    fn distance_sq(&self, x: C, y: C) -> u128 {
+        let axis_distance = |value: C, low: C, high: C| -> u128 {
+            if value < low {
+                wide(low).abs_diff(wide(value))
+            } else if value > high {
+                wide(value).abs_diff(wide(high))
+            } else {
+                0
+            }
+        };
+
+        let dx = axis_distance(x, self.min_x, self.max_x);
+        let dy = axis_distance(y, self.min_y, self.max_y);
+
+        dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy))
+    }
+}
+
+
+#[derive(Debug)]
+enum Children {
+    // leaf node: positions in the indexed rects slice
+    Rects(Vec<usize>),
+    // inner node: positions in the nodes vector
+    Nodes(Vec<usize>),
+}
+
+
+#[derive(Debug)]
+struct Node<C> {
+    bounds: Bounds<C>,
+    children: Children,
+}
+
+
+// entries of the nearest rect search queue, rects come before
+// nodes at the same distance
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NearestEntry {
+    Rect(usize),
+    Node(usize),
+}
+
+
+/// Static R-tree over a slice of rects, bulk loaded using
+/// Sort-Tile-Recursive packing.
+///
+/// Empty (zero sized) rects cover no cells, so they are never
+/// returned by any of the queries.
+pub struct RectIndex<'a, C = u32, I = RectIDType> {
+    rects: &'a [Rect<C, I>],
+    nodes: Vec<Node<C>>,
+    root: Option<usize>,
+}
+
+
+impl<'a, C: Coordinate, I: ClaimID> RectIndex<'a, C, I> {
+    pub fn new(rects: &'a [Rect<C, I>]) -> RectIndex<'a, C, I> {
+        let mut index = RectIndex { rects, nodes: vec![], root: None };
+
+        let entries: Vec<(Bounds<C>, usize)> = rects
+            .iter()
+            .enumerate()
+            .filter(|(_, rect)| !rect.is_empty())
+            .map(|(pos, rect)| (Bounds::of_rect(rect), pos))
+            .collect();
+
+        if entries.is_empty() {
+            return index;
+        }
+
+        // build the tree bottom up, one level at a time
+        let mut level = index.pack(entries, Children::Rects);
+
+        while level.len() > 1 {
+            level = index.pack(level, Children::Nodes);
+        }
+
+        index.root = level.first().map(|&(_, node)| node);
+
+        index
+    }
+
+    fn pack(&mut self,
+            mut entries: Vec<(Bounds<C>, usize)>,
+            make_children: fn(Vec<usize>) -> Children) -> Vec<(Bounds<C>, usize)> {
+        // sort into vertical slices by x, then tile each slice by y
+        let num_nodes = entries.len().div_ceil(NODE_CAPACITY);
+        let num_slices = (num_nodes as f64).sqrt().ceil() as usize;
+        let slice_len = num_slices * NODE_CAPACITY;
+
+        entries.sort_by_key(|(bounds, _)| bounds.center_x());
+
+        let mut packed = vec![];
+
+        for slice in entries.chunks_mut(slice_len) {
+            slice.sort_by_key(|(bounds, _)| bounds.center_y());
+
+            for group in slice.chunks(NODE_CAPACITY) {
+                let bounds = group
+                    .iter()
+                    .skip(1)
+                    .fold(group[0].0, |acc, (bounds, _)| acc.union(bounds));
+
+                let children = group.iter().map(|&(_, pos)| pos).collect();
+
+                self.nodes.push(Node { bounds, children: make_children(children) });
+                packed.push((bounds, self.nodes.len() - 1));
+            }
+        }
+
+        packed
+    }
+
+    fn search(&self, area: Option<Bounds<C>>) -> Search<'_, 'a, C, I> {
+        let nodes = match (area, self.root) {
+            (Some(area), Some(root)) if self.nodes[root].bounds.intersects(&area) => vec![root],
+            _ => vec![],
+        };
+
+        Search {
+            index: self,
+            // never matches anything when there's nothing to visit
+            area: area.unwrap_or_else(|| Bounds::point(C::zero(), C::zero())),
+            nodes,
+            found: vec![],
+        }
+    }
+
+    fn search_rect(&self, rect: &Rect<C, I>) -> Search<'_, 'a, C, I> {
+        if rect.is_empty() {
+            self.search(None)
+        } else {
+            self.search(Some(Bounds::of_rect(rect)))
+        }
+    }
+
+    /// Rects covering the given cell.
+    pub fn query_point(&self, x: C, y: C) -> impl Iterator<Item=&'a Rect<C, I>> + '_ {
+        let rects = self.rects;
+
+        self.search(Some(Bounds::point(x, y)))
+            .map(move |pos| &rects[pos])
+    }
+
+    /// Rects sharing at least one cell with the given region.
+    pub fn query_region(&self, region: &Rect<C, I>)
+                        -> impl Iterator<Item=&'a Rect<C, I>> + '_ {
+        let rects = self.rects;
+
+        self.search_rect(region)
+            .map(move |pos| &rects[pos])
+    }
+
+    /// All pairs of intersecting rects, each pair reported once,
+    /// in the order the rects were indexed.
+    pub fn iter_intersecting_pairs(&self)
+                                   -> impl Iterator<Item=(&'a Rect<C, I>, &'a Rect<C, I>)> + '_ {
+        let rects = self.rects;
+
+        rects
+            .iter()
+            .enumerate()
+            .flat_map(move |(pos, rect)| {
+                self.search_rect(rect)
+                    .filter(move |&other| other > pos)
+                    .map(move |other| (rect, &rects[other]))
+            })
+    }
+
+    /// Rects which don't intersect any other rect.
+    pub fn iter_isolated_rects(&self) -> impl Iterator<Item=&'a Rect<C, I>> + '_ {
+        self.rects
+            .iter()
+            .enumerate()
+            .filter(move |&(pos, rect)| {
+                self.search_rect(rect).all(|other| other == pos)
+            })
+            .map(|(_, rect)| rect)
+    }
+
+    /// One of the rects closest (by euclidean distance) to the given
+    /// cell, rects covering the cell having a distance of 0.
+    pub fn nearest(&self, x: C, y: C) -> Option<&'a Rect<C, I>> {
+        let root = self.root?;
+
+        // best-first search, always expanding the closest entry
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((self.nodes[root].bounds.distance_sq(x, y), NearestEntry::Node(root))));
+
+        while let Some(Reverse((_, entry))) = queue.pop() {
+            match entry {
+                NearestEntry::Rect(pos) => return Some(&self.rects[pos]),
+                NearestEntry::Node(node) => {
+                    match &self.nodes[node].children {
+                        Children::Rects(positions) => {
+                            for &pos in positions {
+                                let distance = Bounds::of_rect(&self.rects[pos]).distance_sq(x, y);
+                                queue.push(Reverse((distance, NearestEntry::Rect(pos))));
+                            }
+                        },
+                        Children::Nodes(nodes) => {
+                            for &child in nodes {
+                                let distance = self.nodes[child].bounds.distance_sq(x, y);
+                                queue.push(Reverse((distance, NearestEntry::Node(child))));
+                            }
+                        },
+                    }
+                },
+            }
+        }
+
+        None
+    }
+}
+
+
+// depth-first traversal yielding positions of rects intersecting an area
+struct Search<'i, 'a, C, I> {
+    index: &'i RectIndex<'a, C, I>,
+    area: Bounds<C>,
+    nodes: Vec<usize>,
+    found: Vec<usize>,
+}
+
+
+impl<'i, 'a, C: Coordinate, I: ClaimID> Iterator for Search<'i, 'a, C, I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(pos) = self.found.pop() {
+                return Some(pos);
+            }
+
+            let node = &self.index.nodes[self.nodes.pop()?];
+            let area = self.area;
+
+            match &node.children {
+                Children::Rects(positions) => {
+                    let rects = self.index.rects;
+
+                    // reversed, so that popping yields rects in tree order
+                    self.found.extend(
+                        positions
+                            .iter()
+                            .rev()
+                            .filter(|&&pos| Bounds::of_rect(&rects[pos]).intersects(&area))
+                    );
+                },
+                Children::Nodes(children) => {
+                    let nodes = &self.index.nodes;
+
+                    self.nodes.extend(
+                        children
+                            .iter()
+                            .filter(|&&child| nodes[child].bounds.intersects(&area))
+                    );
+                },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::rect::{Rect, RectIDType};
+
+    use super::RectIndex;
+
+    fn example_rects() -> Vec<Rect> {
+        vec![
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+            Rect { id: 4, x: 1, y: 1, width: 0, height: 0 },
+        ]
+    }
+
+    // deterministic pseudo-random rects, enough to build a multi-level tree
+    fn generated_rects(count: u32) -> Vec<Rect> {
+        let mut state: u64 = 42;
+
+        let mut next = move |modulo: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) % modulo) as u32
+        };
+
+        (1..=count)
+            .map(|id| Rect { id, x: next(500), y: next(500), width: next(30), height: next(30) })
+            .collect()
+    }
+
+    fn ids<'a>(rects: impl Iterator<Item=&'a Rect>) -> HashSet<RectIDType> {
+        rects.map(|rect| rect.id).collect()
+    }
+
+    #[test]
+    fn test_query_point() {
+        let rects = example_rects();
+        let index = RectIndex::new(&rects);
+
+        assert_eq!(ids(index.query_point(4, 4)), [1, 2].iter().cloned().collect());
+        assert_eq!(ids(index.query_point(5, 5)), [3].iter().cloned().collect());
+        assert_eq!(ids(index.query_point(5, 4)), [2].iter().cloned().collect());
+        assert_eq!(ids(index.query_point(1, 6)), [1].iter().cloned().collect());
+        assert!(ids(index.query_point(1, 1)).is_empty());
+        assert!(ids(index.query_point(100, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_query_region() {
+        let rects = example_rects();
+        let index = RectIndex::new(&rects);
+
+        let region = Rect { id: 0, x: 0, y: 0, width: 4, height: 4 };
+        assert_eq!(ids(index.query_region(&region)), [1, 2].iter().cloned().collect());
+
+        let region = Rect { id: 0, x: 0, y: 0, width: 3, height: 4 };
+        assert_eq!(ids(index.query_region(&region)), [1].iter().cloned().collect());
+
+        let region = Rect { id: 0, x: 6, y: 6, width: 10, height: 10 };
+        assert_eq!(ids(index.query_region(&region)), [3].iter().cloned().collect());
+
+        let empty_region = Rect { id: 0, x: 4, y: 4, width: 0, height: 10 };
+        assert!(ids(index.query_region(&empty_region)).is_empty());
+    }
+
+    #[test]
+    fn test_intersecting_pairs() {
+        let rects = example_rects();
+        let index = RectIndex::new(&rects);
+
+        let pairs: Vec<_> = index
+            .iter_intersecting_pairs()
+            .map(|(left, right)| (left.id, right.id))
+            .collect();
+
+        assert_eq!(pairs, vec![(1, 2)]);
+    }
+
+    #[test]
+    fn test_isolated_rects() {
+        let mut rects = example_rects();
+        rects.push(Rect { id: 5, x: 20, y: 20, width: 2, height: 2 });
+
+        let index = RectIndex::new(&rects);
+
+        let isolated: Vec<_> = index
+            .iter_isolated_rects()
+            .map(|rect| rect.id)
+            .collect();
+
+        // empty rects intersect nothing
+        assert_eq!(isolated, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let rects = example_rects();
+        let index = RectIndex::new(&rects);
+
+        assert_eq!(index.nearest(1, 6).map(|rect| rect.id), Some(1));
+        assert_eq!(index.nearest(20, 20).map(|rect| rect.id), Some(3));
+        assert_eq!(index.nearest(6, 0).map(|rect| rect.id), Some(2));
+        assert_eq!(index.nearest(0, 10).map(|rect| rect.id), Some(1));
+    }
+
+    #[test]
+    fn test_signed_coordinates() {
+        let rects: Vec<Rect<i64, String>> = vec![
+            Rect { id: "west".into(), x: -10, y: -2, width: 4, height: 4 },
+            Rect { id: "east".into(), x: 3, y: -2, width: 4, height: 4 },
+            Rect { id: "hall".into(), x: -8, y: 0, width: 12, height: 1 },
+        ];
+
+        let index = RectIndex::new(&rects);
+
+        let pairs: Vec<_> = index
+            .iter_intersecting_pairs()
+            .map(|(left, right)| (left.id.as_str(), right.id.as_str()))
+            .collect();
+
+        assert_eq!(pairs, vec![("west", "hall"), ("east", "hall")]);
+        assert_eq!(index.nearest(-20, -1).map(|rect| rect.id.as_str()), Some("west"));
+        assert_eq!(index.nearest(i64::MAX, 0).map(|rect| rect.id.as_str()), Some("east"));
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let rects = vec![Rect { id: 1, x: 1, y: 1, width: 0, height: 3 }];
+        let index = RectIndex::new(&rects);
+
+        assert!(index.query_point(1, 1).next().is_none());
+        assert!(index.iter_intersecting_pairs().next().is_none());
+        assert_eq!(index.nearest(1, 1), None);
+        assert_eq!(index.iter_isolated_rects().count(), 1);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let rects = generated_rects(300);
+        let index = RectIndex::new(&rects);
+
+        let expected_pairs: HashSet<_> = rects
+            .iter()
+            .enumerate()
+            .flat_map(|(pos, rect)| {
+                rects[pos + 1..]
+                    .iter()
+                    .filter(move |other| rect.intersects(other))
+                    .map(move |other| (rect.id, other.id))
+            })
+            .collect();
+
+        let pairs: HashSet<_> = index
+            .iter_intersecting_pairs()
+            .map(|(left, right)| (left.id, right.id))
+            .collect();
+
+        assert_eq!(pairs.len(), index.iter_intersecting_pairs().count());
+        assert_eq!(pairs, expected_pairs);
+
+        for &(x, y) in &[(0, 0), (250, 250), (499, 3), (510, 510)] {
+            let expected_ids = ids(
+                rects.iter().filter(|rect| rect.iter_coords().any(|coord| coord == (x, y)))
+            );
+
+            assert_eq!(ids(index.query_point(x, y)), expected_ids);
+
+            let nearest = index.nearest(x, y).unwrap();
+            let distance = |rect: &Rect| super::Bounds::of_rect(rect).distance_sq(x, y);
+
+            let expected_distance = rects
+                .iter()
+                .filter(|rect| !rect.is_empty())
+                .map(distance)
+                .min();
+
+            assert_eq!(Some(distance(nearest)), expected_distance);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::rect::{ClaimID, Coordinate, InvalidRect, Rect};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    // one claim per line, as produced by `Rect`'s `Display`
+    Text,
+    // array of objects with `id`, `x`, `y`, `width` and `height`
+    Json,
+    // `id,x,y,width,height` header and one claim per record
+    Csv,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Unknown input format: '{}'", format)]
+pub struct UnknownInputFormat {
+    format: String,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "{} requires the serde feature", usage)]
+pub struct SerdeDisabled {
+    pub usage: String,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ReadClaimsError {
+    #[fail(display = "{}", _0)]
+    SerdeDisabled(SerdeDisabled),
+
+    #[fail(display = "Error parsing {} claims: {}", format, message)]
+    Invalid {
+        format: InputFormat,
+        message: String,
+    },
+
+    #[fail(display = "{}", _0)]
+    InvalidRect(InvalidRect),
+}
+
+
+impl InputFormat {
+    /// Format matching the file extension, text for anything else.
+    pub fn from_path(path: impl AsRef<Path>) -> InputFormat {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some("json") => InputFormat::Json,
+            Some("csv") => InputFormat::Csv,
+            _ => InputFormat::Text,
+        }
+    }
+}
+
+
+impl FromStr for InputFormat {
+    type Err = UnknownInputFormat;
+
+    fn from_str(s: &str) -> Result<InputFormat, UnknownInputFormat> {
+        match s {
+            "text" => Ok(InputFormat::Text),
+            "json" => Ok(InputFormat::Json),
+            "csv" => Ok(InputFormat::Csv),
+            _ => Err(UnknownInputFormat { format: s.into() }),
+        }
+    }
+}
+
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            InputFormat::Text => "text",
+            InputFormat::Json => "JSON",
+            InputFormat::Csv => "CSV",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+
+/// Claims from JSON or CSV input, checked like the ones parsed from
+/// text.  Text input is parsed line by line, see `Rect::parse`.
+pub fn read_claims<C, I>(input: &str, format: InputFormat) -> Result<Vec<Rect<C, I>>, ReadClaimsError>
+    where C: Coordinate, I: ClaimID
+{
+    let rects = deserialize_claims(input, format)?;
+
+    for rect in &rects {
+        rect.check_bounds().map_err(ReadClaimsError::InvalidRect)?;
+    }
+
+    Ok(rects)
+}
+
+
+#[cfg(feature = "serde")]
+fn deserialize_claims<C, I>(input: &str, format: InputFormat) -> Result<Vec<Rect<C, I>>, ReadClaimsError>
+    where C: Coordinate, I: ClaimID
+{
+    let invalid = |message: String| ReadClaimsError::Invalid { format, message };
+
+    match format {
+        InputFormat::Json => serde_json::from_str(input).map_err(|err| invalid(err.to_string())),
+        InputFormat::Csv => {
+            csv::Reader::from_reader(input.as_bytes())
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(|err| invalid(err.to_string()))
+        },
+        InputFormat::Text => Err(invalid("text claims are parsed line by line".into())),
+    }
+}
+
+
+#[cfg(not(feature = "serde"))]
+fn deserialize_claims<C, I>(_input: &str, format: InputFormat) -> Result<Vec<Rect<C, I>>, ReadClaimsError>
+    where C: Coordinate, I: ClaimID
+{
+    Err(ReadClaimsError::SerdeDisabled(SerdeDisabled { usage: format!("Reading {} claims", format) }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    use crate::rect::Rect;
+
+    use super::{read_claims, InputFormat};
+
+    #[test]
+    fn test_input_format() {
+        assert_eq!(InputFormat::from_path("claims.json"), InputFormat::Json);
+        assert_eq!(InputFormat::from_path("input/claims.csv"), InputFormat::Csv);
+        assert_eq!(InputFormat::from_path("input/input"), InputFormat::Text);
+
+        assert_eq!("csv".parse::<InputFormat>().unwrap(), InputFormat::Csv);
+
+        let err = "yaml".parse::<InputFormat>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown input format: 'yaml'");
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn test_serde_disabled() {
+        let err = read_claims::<u32, u32>("[]", InputFormat::Json).unwrap_err();
+        assert_eq!(format!("{}", err), "Reading JSON claims requires the serde feature");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_read_json() {
+        let input = r#"[
+            {"id": 1, "x": 1, "y": 3, "width": 4, "height": 4},
+            {"id": 2, "x": 3, "y": 1, "width": 4, "height": 4}
+        ]"#;
+
+        assert_eq!(
+            read_claims(input, InputFormat::Json).unwrap(),
+            vec![
+                Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+                Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            ]
+        );
+
+        let rects = read_claims::<i32, String>(r#"[{"id": "hall", "x": -1, "y": 0, "width": 2, "height": 1}]"#,
+                                               InputFormat::Json).unwrap();
+        assert_eq!(rects, vec![Rect { id: "hall".into(), x: -1, y: 0, width: 2, height: 1 }]);
+
+        let err = read_claims::<u32, u32>(r#"[{"id": 1, "x": 1}]"#, InputFormat::Json).unwrap_err();
+        assert!(format!("{}", err).starts_with("Error parsing JSON claims: missing field `y`"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_read_csv() {
+        let input = "id,x,y,width,height\n1,1,3,4,4\n2,3,1,4,4\n";
+
+        assert_eq!(
+            read_claims(input, InputFormat::Csv).unwrap(),
+            vec![
+                Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+                Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            ]
+        );
+
+        let err = read_claims::<u32, u32>("id,x,y,width,height\n1,1,3,4\n", InputFormat::Csv).unwrap_err();
+        assert!(format!("{}", err).starts_with("Error parsing CSV claims: "));
+
+        // checked like parsed claims
+        let err = read_claims::<u32, u32>("id,x,y,width,height\n1,4294967295,3,4,4\n", InputFormat::Csv)
+            .unwrap_err();
+        assert_eq!(format!("{}", err), "Rect 1 exceeds the coordinate space: x + width overflows");
+    }
+}
@@ -0,0 +1,309 @@
+use common::summed_area::SummedArea;
+use common::Fail;
+use num_traits::NumCast;
+
+use crate::overlaps::{check_fabric_size, FabricSize, FabricTooLarge, Overlaps};
+use crate::rect::{check_unique_ids, wide, ClaimID, Coordinate, DuplicateClaimID, Rect, RectIDType};
+
+
+// number of times all the claims are reconsidered, at most
+const MAX_PASSES: usize = 8;
+
+
+/// Claim moved by the layout optimiser, from its original position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move<C = u32, I = RectIDType> {
+    pub id: I,
+    pub from: (C, C),
+    pub to: (C, C),
+}
+
+
+/// Claims rearranged on a fabric, in the order they were given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout<C = u32, I = RectIDType> {
+    pub rects: Vec<Rect<C, I>>,
+    pub moves: Vec<Move<C, I>>,
+    pub overlap_area_before: usize,
+    pub overlap_area: usize,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum LayoutError {
+    #[fail(display = "{}", _0)]
+    FabricTooLarge(FabricTooLarge),
+
+    // claims are told apart by ID while moving them
+    #[fail(display = "{}", _0)]
+    DuplicateClaimID(DuplicateClaimID),
+}
+
+
+// ordered so that the smallest score is the best position: first
+// the least overlap area added, then the least claimed cells covered,
+// then the least displacement
+type Score = (u32, u32, u128);
+
+
+// whether a position without any overlap was found, which further
+// positions can't beat
+fn is_unbeatable(best: &Option<(Score, usize, usize)>, displacement: u128) -> bool {
+    matches!(best, Some(((0, 0, best_displacement), _, _)) if displacement > *best_displacement)
+}
+
+
+// all positions from 0 to `max`, closest to `target` first
+fn closest_first(target: i128, max: usize) -> Vec<usize> {
+    let mut positions: Vec<usize> = (0..=max).collect();
+
+    positions.sort_by_key(|&position| (position as i128 - target).abs());
+    positions
+}
+
+
+fn displacement<C: Coordinate>(from: (C, C), to: (C, C)) -> u128 {
+    let dx = wide(from.0).abs_diff(wide(to.0));
+    let dy = wide(from.1).abs_diff(wide(to.1));
+
+    dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy))
+}
+
+
+/// Move claims around a fabric of the given size, keeping their sizes,
+/// to minimise the total overlap area, preferring positions closest to
+/// the original ones.
+///
+/// This is a greedy search: claims are repeatedly taken off the fabric
+/// and put back at their best position against all the others, until
+/// no claim can be improved.  Claims which don't fit on the fabric
+/// are left where they are.
+pub fn optimise_layout<C, I>(rects: &[Rect<C, I>], fabric: FabricSize<C>)
+                             -> Result<Layout<C, I>, LayoutError>
+    where C: Coordinate, I: ClaimID
+{
+    check_unique_ids(rects).map_err(LayoutError::DuplicateClaimID)?;
+
+    // depth can't exceed the number of claims, which fits in 32 bits,
+    // with the two summed-area tables scoring positions
+    check_fabric_size::<u32, C>(fabric, 2).map_err(LayoutError::FabricTooLarge)?;
+
+    let mut overlaps = Overlaps::<u32, C, I>::with_fabric(rects, fabric)
+        .map_err(LayoutError::FabricTooLarge)?;
+    let overlap_area_before = overlaps.overlap_area();
+
+    let (grid_width, grid_height) = overlaps.grid_size();
+    let mut positions: Vec<(C, C)> = rects.iter().map(|rect| (rect.x, rect.y)).collect();
+
+    // refilled for each claim, the cells fitting in a u32 like the depths
+    let mut newly_overlapped = SummedArea::new(grid_width, grid_height, |_, _| 0_u32);
+    let mut claimed = SummedArea::new(grid_width, grid_height, |_, _| 0_u32);
+
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+
+        for (pos, rect) in rects.iter().enumerate() {
+            let (width, height) = match (rect.width.to_usize(), rect.height.to_usize()) {
+                (Some(width), Some(height))
+                    if width > 0 && height > 0 && width <= grid_width && height <= grid_height => {
+                    (width, height)
+                },
+                _ => continue,
+            };
+
+            let original = (rect.x, rect.y);
+            let current = Rect { x: positions[pos].0, y: positions[pos].1, ..rect.clone() };
+
+            // claims partially off the fabric always move
+            let (top_x, top_y) = current.clamped_top_right();
+
+            let current_cell = match (overlaps.grid_position(current.x, current.y),
+                                      overlaps.grid_position(top_x, top_y)) {
+                (Some(cell), Some(_)) => Some(cell),
+                _ => None,
+            };
+
+            // nothing to improve on claims overlapping nothing
+            if current_cell.is_some() &&
+                current.iter_coords().all(|(x, y)| overlaps.depth_at(x, y) == 1) {
+                continue;
+            }
+
+            // score positions against the other claims only
+            overlaps.remove(&rect.id);
+
+            newly_overlapped.refill(|x, y| (overlaps.depth_at_cell(x, y) == 1) as u32);
+            claimed.refill(|x, y| (overlaps.depth_at_cell(x, y) >= 1) as u32);
+
+            let to_coords = |x: usize, y: usize| -> (C, C) {
+                // within the fabric, so always representable
+                (<C as NumCast>::from(x).expect("fabric cells fit in coordinates"),
+                 <C as NumCast>::from(y).expect("fabric cells fit in coordinates"))
+            };
+
+            let score = |x: usize, y: usize| -> Score {
+                (newly_overlapped.sum(x, y, width, height),
+                 claimed.sum(x, y, width, height),
+                 displacement(original, to_coords(x, y)))
+            };
+
+            let current_score = current_cell.map(|(x, y)| score(x, y));
+
+            // visit positions closest to the original one first, so that
+            // the search stops as soon as nothing closer is left
+            let columns = closest_first(wide(original.0), grid_width - width);
+            let rows = closest_first(wide(original.1), grid_height - height);
+
+            let mut best: Option<(Score, usize, usize)> = None;
+
+            for &y in &rows {
+                let row_displacement = displacement(original, to_coords(columns[0], y));
+
+                if is_unbeatable(&best, row_displacement) {
+                    break;
+                }
+
+                for &x in &columns {
+                    let candidate = score(x, y);
+
+                    if is_unbeatable(&best, candidate.2) {
+                        break;
+                    }
+
+                    if best.is_none_or(|(best_score, _, _)| candidate < best_score) {
+                        best = Some((candidate, x, y));
+                    }
+                }
+            }
+
+            let best = best.map(|(best_score, x, y)| (best_score, to_coords(x, y)));
+
+            let new_rect = match (best, current_score) {
+                (Some((best_score, to)), current_score)
+                    if current_score.is_none_or(|current_score| best_score < current_score) => {
+                    moved = true;
+                    positions[pos] = to;
+
+                    Rect { x: to.0, y: to.1, ..rect.clone() }
+                },
+                _ => current,
+            };
+
+            // claims on a fabric never exceed the grid
+            overlaps.add(&new_rect).expect("fabric claims always fit");
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    let moves = rects
+        .iter()
+        .zip(&positions)
+        .filter(|(rect, &to)| (rect.x, rect.y) != to)
+        .map(|(rect, &to)| Move { id: rect.id.clone(), from: (rect.x, rect.y), to })
+        .collect();
+
+    let rects = rects
+        .iter()
+        .zip(&positions)
+        .map(|(rect, &(x, y))| Rect { x, y, ..rect.clone() })
+        .collect();
+
+    Ok(Layout { rects, moves, overlap_area_before, overlap_area: overlaps.overlap_area() })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::overlaps::FabricSize;
+    use crate::rect::Rect;
+
+    use super::{optimise_layout, Move};
+
+    #[test]
+    fn test_moves_closest_free_position() {
+        let rects = [
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 2 },
+            Rect { id: 2, x: 0, y: 0, width: 2, height: 2 },
+        ];
+
+        let layout = optimise_layout(&rects, FabricSize { width: 5, height: 2 }).unwrap();
+
+        assert_eq!(layout.overlap_area_before, 4);
+        assert_eq!(layout.overlap_area, 0);
+        assert_eq!(layout.moves, vec![Move { id: 1, from: (0, 0), to: (2, 0) }]);
+
+        assert_eq!(
+            layout.rects,
+            vec![
+                Rect { id: 1, x: 2, y: 0, width: 2, height: 2 },
+                Rect { id: 2, x: 0, y: 0, width: 2, height: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_moves_claims_onto_the_fabric() {
+        let rects = [
+            Rect { id: 1, x: 7, y: 7, width: 2, height: 2 },
+            // too large to ever fit
+            Rect { id: 2, x: 0, y: 0, width: 9, height: 1 },
+        ];
+
+        let layout = optimise_layout(&rects, FabricSize { width: 8, height: 8 }).unwrap();
+
+        assert_eq!(layout.moves, vec![Move { id: 1, from: (7, 7), to: (6, 6) }]);
+    }
+
+    #[test]
+    fn test_removes_example_overlaps() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+        ];
+
+        let layout = optimise_layout(&rects, FabricSize { width: 8, height: 8 }).unwrap();
+
+        assert_eq!(layout.overlap_area_before, 4);
+        assert_eq!(layout.overlap_area, 0);
+
+        for (rect, original) in layout.rects.iter().zip(&rects) {
+            assert_eq!((rect.id, rect.width, rect.height), (original.id, original.width, original.height));
+            assert!(Rect { id: 0, x: 0, y: 0, width: 8, height: 8 }.contains_rect(rect));
+        }
+
+        for (pos, rect) in layout.rects.iter().enumerate() {
+            assert!(layout.rects[pos + 1..].iter().all(|other| !rect.intersects(other)));
+        }
+    }
+
+    #[test]
+    fn test_keeps_overlaps_when_crowded() {
+        let rects = [
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 2 },
+            Rect { id: 2, x: 1, y: 1, width: 2, height: 2 },
+        ];
+
+        // no room to avoid each other, nothing better to do
+        let layout = optimise_layout(&rects, FabricSize { width: 3, height: 3 }).unwrap();
+
+        assert_eq!(layout.overlap_area, 1);
+        assert!(layout.moves.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let rects = [
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 2 },
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 2 },
+        ];
+
+        let err = optimise_layout(&rects, FabricSize { width: 5, height: 5 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Duplicate claim ID 1 on lines 1 and 2");
+    }
+}
@@ -0,0 +1,7 @@
+pub mod index;
+pub mod input;
+pub mod layout;
+pub mod overlaps;
+pub mod rect;
+pub mod render;
+pub mod report;
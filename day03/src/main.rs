@@ -1,36 +1,297 @@
 use std::env;
+use std::fs;
+use std::io::{self, Write};
 
-use common::{get_input, Error};
+use common::{get_input_text, get_input_with, parse_options, Error, InvalidOption};
 
-mod rect;
-mod overlaps;
+use day03::index::RectIndex;
+#[cfg(not(feature = "serde"))]
+use day03::input::SerdeDisabled;
+use day03::input::{read_claims, InputFormat};
+use day03::layout::optimise_layout;
+use day03::overlaps::{CellType, Count, FabricSize, FillMethod, Overlaps};
+use day03::rect::{check_unique_ids, ClaimID, Coordinate, CoordinateType, ParseMode, Rect, RectIDType};
+use day03::render::{render_ansi, render_ppm, RenderFormat};
+use day03::report::{format_reports, Answers, ReportFormat};
 
-use crate::rect::Rect;
-use crate::overlaps::RectOverlaps;
+
+// default width of the terminal heatmap, in characters
+const DEFAULT_RENDER_WIDTH: usize = 80;
+
+
+struct Settings {
+    input_format: InputFormat,
+    parse_mode: ParseMode,
+    cell_type: Option<CellType>,
+    fill: FillMethod,
+    // parsed once the coordinate type is known
+    fabric: Option<String>,
+    report_format: Option<ReportFormat>,
+    render_format: Option<RenderFormat>,
+    render_width: usize,
+    // file for the render or the optimised claims, instead of stdout
+    output: Option<String>,
+    labels: bool,
+    threshold: Option<usize>,
+    histogram: bool,
+    json: bool,
+    optimise: bool,
+}
 
 
 fn main() -> Result<(), Error> {
     let args: Vec<String> = env::args().collect();
-    let input = get_input(&args);
 
-    if input.is_err() {
-        eprintln!("Error: {}. Aborting.", input.unwrap_err());
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
         std::process::exit(1);
     }
 
-    let rects: Vec<Rect> = input.unwrap();
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(
+        args,
+        &["cell-type", "coordinates", "fabric", "fill", "input-format", "output", "render", "report", "threshold", "width"],
+        &["histogram", "json", "labels", "optimise", "strict", "string-ids"]
+    )?;
+
+    // the input file extension tells the format, unless given
+    let input_format = match options.value("input-format")? {
+        Some(format) => format,
+        None if args.len() == 2 => InputFormat::from_path(&args[1]),
+        None => InputFormat::Text,
+    };
+
+    let settings = Settings {
+        input_format,
+        parse_mode: if options.flag("strict") { ParseMode::Strict } else { ParseMode::Lenient },
+        cell_type: options.value("cell-type")?,
+        fill: options.value_or("fill", FillMethod::Cells)?,
+        fabric: options.value("fabric")?,
+        report_format: options.value("report")?,
+        render_format: options.value("render")?,
+        render_width: options.value_or("width", DEFAULT_RENDER_WIDTH)?,
+        output: options.value("output")?,
+        labels: options.flag("labels"),
+        threshold: options.value("threshold")?,
+        histogram: options.flag("histogram"),
+        json: options.flag("json"),
+        optimise: options.flag("optimise"),
+    };
+
+    let coordinate_type = options.value_or("coordinates", CoordinateType::U32)?;
+
+    match (coordinate_type, options.flag("string-ids")) {
+        (CoordinateType::U32, false) => solve::<u32, RectIDType>(&args, &settings),
+        (CoordinateType::U64, false) => solve::<u64, RectIDType>(&args, &settings),
+        (CoordinateType::I32, false) => solve::<i32, RectIDType>(&args, &settings),
+        (CoordinateType::I64, false) => solve::<i64, RectIDType>(&args, &settings),
+        (CoordinateType::U32, true) => solve::<u32, String>(&args, &settings),
+        (CoordinateType::U64, true) => solve::<u64, String>(&args, &settings),
+        (CoordinateType::I32, true) => solve::<i32, String>(&args, &settings),
+        (CoordinateType::I64, true) => solve::<i64, String>(&args, &settings),
+    }
+}
+
+
+fn solve<C, I>(args: &[String], settings: &Settings) -> Result<(), Error>
+    where C: Coordinate, I: ClaimID
+{
+    let fabric = match &settings.fabric {
+        Some(size) => Some(size.parse::<FabricSize<C>>()?),
+        None => None,
+    };
+
+    let rects: Vec<Rect<C, I>> = match settings.input_format {
+        InputFormat::Text => get_input_with(args, |line| Rect::parse(line, settings.parse_mode))?,
+        format => read_claims(&get_input_text(args)?, format)?,
+    };
+    check_unique_ids(&rects)?;
+
+    if settings.optimise {
+        let fabric = fabric.ok_or_else(|| InvalidOption::MissingValue { name: "fabric".into() })?;
+
+        return optimise(&rects, fabric, settings);
+    }
+
+    // pick the count grid cell type so that overlap depth can't saturate
+    let cell_type = match settings.cell_type {
+        Some(cell_type) => cell_type.check_fits(rects.len())?,
+        None => CellType::for_claims(rects.len()),
+    };
+
+    match cell_type {
+        CellType::U8 => answer(&make_overlaps::<u8, C, I>(&rects, fabric, settings.fill)?, &rects, settings),
+        CellType::U16 => answer(&make_overlaps::<u16, C, I>(&rects, fabric, settings.fill)?, &rects, settings),
+        CellType::U32 => answer(&make_overlaps::<u32, C, I>(&rects, fabric, settings.fill)?, &rects, settings),
+        CellType::U64 => answer(&make_overlaps::<u64, C, I>(&rects, fabric, settings.fill)?, &rects, settings),
+    }
+}
+
+
+fn optimise<C, I>(rects: &[Rect<C, I>], fabric: FabricSize<C>, settings: &Settings)
+                  -> Result<(), Error>
+    where C: Coordinate, I: ClaimID
+{
+    let layout = optimise_layout(rects, fabric)?;
+
+    // the claims can be fed back in, so the report goes to stderr
+    let claims: String = layout.rects
+        .iter()
+        .map(|rect| format!("{}\n", rect))
+        .collect();
+
+    match &settings.output {
+        Some(path) => fs::write(path, claims)?,
+        None => print!("{}", claims),
+    }
+
+    for claim_move in &layout.moves {
+        eprintln!("Moved claim {} from {},{} to {},{}",
+                  claim_move.id, claim_move.from.0, claim_move.from.1, claim_move.to.0, claim_move.to.1);
+    }
+
+    eprintln!("Overlap area: {} -> {}", layout.overlap_area_before, layout.overlap_area);
+
+    Ok(())
+}
+
+
+#[cfg(feature = "serde")]
+fn answers_json<I: ClaimID>(answers: &Answers<I>) -> Result<String, Error> {
+    Ok(serde_json::to_string(answers)?)
+}
+
+
+#[cfg(not(feature = "serde"))]
+fn answers_json<I: ClaimID>(_answers: &Answers<I>) -> Result<String, Error> {
+    Err(SerdeDisabled { usage: "JSON output".into() }.into())
+}
+
 
-    let overlaps = RectOverlaps::new(&rects);
+fn make_overlaps<T, C, I>(rects: &[Rect<C, I>], fabric: Option<FabricSize<C>>, fill: FillMethod)
+                          -> Result<Overlaps<T, C, I>, Error>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    let overlaps = match (fill, fabric) {
+        (FillMethod::Cells, _) => fill_cells(rects, fabric)?,
+        (FillMethod::Differences, Some(size)) => Overlaps::diff_with_fabric(rects, size)?,
+        (FillMethod::Differences, None) => Overlaps::diff_new(rects)?,
+    };
+
+    Ok(overlaps)
+}
+
+
+#[cfg(not(feature = "parallel"))]
+fn fill_cells<T, C, I>(rects: &[Rect<C, I>], fabric: Option<FabricSize<C>>)
+                       -> Result<Overlaps<T, C, I>, Error>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    let overlaps = match fabric {
+        Some(size) => Overlaps::with_fabric(rects, size)?,
+        None => Overlaps::new(rects)?,
+    };
+
+    Ok(overlaps)
+}
+
+
+#[cfg(feature = "parallel")]
+fn fill_cells<T, C, I>(rects: &[Rect<C, I>], fabric: Option<FabricSize<C>>)
+                       -> Result<Overlaps<T, C, I>, Error>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    let overlaps = match fabric {
+        Some(size) => Overlaps::par_with_fabric(rects, size)?,
+        None => Overlaps::par_new(rects)?,
+    };
+
+    Ok(overlaps)
+}
+
+
+#[cfg(not(feature = "parallel"))]
+fn non_overlapping_ids<T, C, I>(_overlaps: &Overlaps<T, C, I>, rects: &[Rect<C, I>]) -> Vec<I>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    isolated_ids(rects)
+}
+
+
+// the count grid only covers all of every claim without a fabric,
+// claims sticking out of one are checked with the index instead
+#[cfg(feature = "parallel")]
+fn non_overlapping_ids<T, C, I>(overlaps: &Overlaps<T, C, I>, rects: &[Rect<C, I>]) -> Vec<I>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    if overlaps.fabric().is_some() {
+        return isolated_ids(rects);
+    }
+
+    overlaps
+        .par_non_overlapping_rects()
+        .into_iter()
+        .map(|rect| rect.id.clone())
+        .collect()
+}
+
+
+fn isolated_ids<C: Coordinate, I: ClaimID>(rects: &[Rect<C, I>]) -> Vec<I> {
+    RectIndex::new(rects)
+        .iter_isolated_rects()
+        .map(|rect| rect.id.clone())
+        .collect()
+}
+
+
+fn answer<T, C, I>(overlaps: &Overlaps<T, C, I>, rects: &[Rect<C, I>], settings: &Settings)
+                   -> Result<(), Error>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    // per-claim report and renders replace the answers,
+    // to keep the output parseable
+    if let Some(format) = settings.report_format {
+        print!("{}", format_reports(&overlaps.claim_reports(), format));
+        return Ok(());
+    }
+
+    if let Some(format) = settings.render_format {
+        let rendered = match format {
+            RenderFormat::Ppm => render_ppm(overlaps, settings.labels)?,
+            RenderFormat::Ansi => {
+                render_ansi(overlaps, settings.render_width, settings.labels)?.into_bytes()
+            },
+        };
+
+        match &settings.output {
+            Some(path) => fs::write(path, rendered)?,
+            None => io::stdout().write_all(&rendered)?,
+        }
+
+        return Ok(());
+    }
+
+    let answers = Answers {
+        overlap_area: overlaps.overlap_area(),
+        non_overlapping_ids: non_overlapping_ids(overlaps, rects),
+    };
+
+    if settings.json {
+        println!("{}", answers_json(&answers)?);
+        return Ok(());
+    }
 
     // part 1
 
-    println!("Overlap area: {}", overlaps.overlap_area());
+    println!("Overlap area: {}", answers.overlap_area);
 
     // part 2
-    let non_overlap_ids: Vec<_> = overlaps
-        .iter_non_overlapping_rects()
-        .map(|rect| rect.id)
-        .collect();
+
+    let non_overlap_ids = &answers.non_overlapping_ids;
 
     match non_overlap_ids.len() {
         0 => println!("No overlaps"),
@@ -38,5 +299,35 @@ fn main() -> Result<(), Error> {
         _ => println!("Non overlapping claim IDs: {:?}", non_overlap_ids),
     }
 
+    // extras
+
+    if settings.fabric.is_some() {
+        for claim in overlaps.claims_outside_fabric() {
+            if claim.fully_outside {
+                println!("Claim {} is outside the fabric", claim.id);
+            } else {
+                println!("Claim {} is partially outside the fabric ({} cells)",
+                         claim.id, claim.cells_outside);
+            }
+        }
+
+        println!("Unclaimed area: {}", overlaps.unclaimed_area());
+        println!("Fabric utilisation: {:.2}%", overlaps.utilisation());
+    }
+
+    if let Some(threshold) = settings.threshold {
+        println!("Area covered by at least {} claims: {}",
+                 threshold, overlaps.area_with_depth_at_least(threshold));
+    }
+
+    if settings.histogram {
+        println!("Overlap depth histogram:");
+
+        // depth 0 is the unclaimed area, not interesting here
+        for (depth, area) in overlaps.depth_histogram().iter().enumerate().skip(1) {
+            println!("{}: {}", depth, area);
+        }
+    }
+
     Ok(())
 }
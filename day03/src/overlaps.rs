@@ -1,90 +1,755 @@
 use std::cmp::max;
-use std::ops::AddAssign;
+use std::fmt;
+use std::mem;
+use std::ops::{AddAssign, Range};
+use std::ptr;
+use std::str::FromStr;
 
-use ndarray::{Array2, s, ScalarOperand};
 use num_traits::identities::One;
 use num_traits::int::PrimInt;
 
-use crate::rect::Rect;
+use common::grid::Grid;
+use common::summed_area::SummedArea;
+use common::Fail;
 
+use crate::index::RectIndex;
+use crate::rect::{ClaimID, Coordinate, Rect, RectIDType};
+use crate::report::ClaimReport;
 
-pub struct Overlaps<'a, T> {
-    count_grid: Array2<T>,
-    rects: &'a [Rect],
+mod differences;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+
+/// Count grid covering either all the cells claimed by the rects, from
+/// the minimum corner of the claimed cells, or a fixed size fabric.
+///
+/// Counts of the claims given up front saturate rather than overflow,
+/// so removing claims only gives exact counts if the cell type can
+/// count all of them, see `CellType`.  Adding a claim which would
+/// saturate a count fails instead.
+pub struct Overlaps<T, C = u32, I = RectIDType> {
+    count_grid: Grid<T>,
+    // fabric coordinates of the grid's (0, 0) cell
+    origin: (C, C),
+    // fixed fabric, claims being clipped to it
+    fabric: Option<Rect<C, I>>,
+    rects: Vec<Rect<C, I>>,
+    // area covered by each number of claims, following the count grid
+    depth_areas: Vec<usize>,
+    // area covered by at least two claims
+    overlap_cells: usize,
+}
+
+pub type RectOverlaps = Overlaps<u8>;
+
+
+// largest grids to allocate for counting, in bytes
+const MAX_GRID_BYTES: usize = 1 << 30;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Claim {} exceeds fabric bounds", id)]
+pub struct ExceedsFabricBounds {
+    pub id: String,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Claim {} would take cell counts past {}", id, max)]
+pub struct CountOverflow {
+    pub id: String,
+    pub max: u64,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum AddClaimError {
+    #[fail(display = "{}", _0)]
+    ExceedsFabricBounds(ExceedsFabricBounds),
+
+    #[fail(display = "{}", _0)]
+    CountOverflow(CountOverflow),
+}
+
+
+/// Size of a fabric sheet starting at (0, 0), written as e.g. "1000x1000".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FabricSize<C = u32> {
+    pub width: C,
+    pub height: C,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Invalid fabric size: '{}', expected WIDTHxHEIGHT", string)]
+pub struct ParseFabricSizeError {
+    string: String,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Fabric of {} cells is too large", size)]
+pub struct FabricTooLarge {
+    pub size: String,
+}
+
+
+impl<C: Coordinate> FromStr for FabricSize<C> {
+    type Err = ParseFabricSizeError;
+
+    fn from_str(s: &str) -> Result<FabricSize<C>, ParseFabricSizeError> {
+        let invalid = || ParseFabricSizeError { string: s.into() };
+
+        let mut sizes = s.splitn(2, 'x').map(|size| size.parse::<C>().ok());
+
+        match (sizes.next(), sizes.next()) {
+            (Some(Some(width)), Some(Some(height))) if width >= C::zero() && height >= C::zero() => {
+                Ok(FabricSize { width, height })
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+
+impl<C: fmt::Display> fmt::Display for FabricSize<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+
+/// Claim not entirely on the fabric.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutsideClaim<I = RectIDType> {
+    pub id: I,
+    // number of the claim's cells off the fabric
+    pub cells_outside: u64,
+    // none of the claim's cells are on the fabric
+    pub fully_outside: bool,
+}
+
+
+/// Integer types usable as count grid cells.
+pub trait Count: AddAssign + One + PrimInt + Send + Sync {}
+
+impl<T> Count for T where T: AddAssign + One + PrimInt + Send + Sync {}
+
+
+/// Integer type used for the cells of the count grid.  Overlap depth
+/// can't exceed the number of claims, so picking a type which can
+/// hold that number guarantees the counts never saturate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+    U8,
+    U16,
+    U32,
+    U64,
 }
 
-pub type RectOverlaps<'a> = Overlaps<'a, u8>;
 
+#[derive(Debug, Fail)]
+pub enum CellTypeError {
+    #[fail(display = "Unknown cell type: '{}'", cell_type)]
+    Unknown {
+        cell_type: String,
+    },
+
+    #[fail(display = "Cell type {} can't count up to {} claims", cell_type, num_claims)]
+    TooSmall {
+        cell_type: CellType,
+        num_claims: usize,
+    },
+}
+
+
+impl CellType {
+    const ALL: [CellType; 4] = [CellType::U8, CellType::U16, CellType::U32, CellType::U64];
+
+    pub fn max_count(self) -> u64 {
+        match self {
+            CellType::U8 => u64::from(u8::MAX),
+            CellType::U16 => u64::from(u16::MAX),
+            CellType::U32 => u64::from(u32::MAX),
+            CellType::U64 => u64::MAX,
+        }
+    }
+
+    /// Smallest cell type able to count all the claims.
+    pub fn for_claims(num_claims: usize) -> CellType {
+        CellType::ALL
+            .iter()
+            .cloned()
+            .find(|cell_type| cell_type.fits(num_claims))
+            .unwrap_or(CellType::U64)
+    }
+
+    pub fn fits(self, num_claims: usize) -> bool {
+        num_claims as u64 <= self.max_count()
+    }
+
+    pub fn check_fits(self, num_claims: usize) -> Result<CellType, CellTypeError> {
+        if self.fits(num_claims) {
+            Ok(self)
+        } else {
+            Err(CellTypeError::TooSmall { cell_type: self, num_claims })
+        }
+    }
+}
+
+
+impl FromStr for CellType {
+    type Err = CellTypeError;
+
+    fn from_str(s: &str) -> Result<CellType, CellTypeError> {
+        match s {
+            "u8" => Ok(CellType::U8),
+            "u16" => Ok(CellType::U16),
+            "u32" => Ok(CellType::U32),
+            "u64" => Ok(CellType::U64),
+            _ => Err(CellTypeError::Unknown { cell_type: s.into() }),
+        }
+    }
+}
+
+
+impl fmt::Display for CellType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CellType::U8 => "u8",
+            CellType::U16 => "u16",
+            CellType::U32 => "u32",
+            CellType::U64 => "u64",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+
+/// How the count grid is filled: claim by claim, touching every cell
+/// of every claim, or with a difference array, touching 4 cells per
+/// claim and then every cell of the grid once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillMethod {
+    Cells,
+    Differences,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Unknown fill method: '{}'", method)]
+pub struct UnknownFillMethod {
+    method: String,
+}
+
+
+impl FromStr for FillMethod {
+    type Err = UnknownFillMethod;
+
+    fn from_str(s: &str) -> Result<FillMethod, UnknownFillMethod> {
+        match s {
+            "cells" => Ok(FillMethod::Cells),
+            "differences" => Ok(FillMethod::Differences),
+            _ => Err(UnknownFillMethod { method: s.into() }),
+        }
+    }
+}
+
+
+// distance from the grid origin, if it fits in the grid's index type
+fn grid_offset<C: Coordinate>(coord: C, origin: C) -> Option<usize> {
+    coord.checked_sub(&origin)?.to_usize()
+}
+
+
+// whether a count grid of cells of `T` fits in MAX_GRID_BYTES, along
+// with the difference array filling it if there's one and the
+// summed-area table finding the non-overlapping claims
+fn grid_fits<T>(width: usize, height: usize, fill: FillMethod) -> bool {
+    grid_bytes::<T>(width, height, fill).is_some_and(|bytes| bytes <= MAX_GRID_BYTES)
+}
+
+
+// bytes of the grids, `None` if more than fit in a usize
+fn grid_bytes<T>(width: usize, height: usize, fill: FillMethod) -> Option<usize> {
+    let count_bytes = width.checked_mul(height)?.checked_mul(mem::size_of::<T>())?;
+
+    // a row and a column larger than the count grid
+    let difference_bytes = match fill {
+        FillMethod::Cells => 0,
+        FillMethod::Differences => width
+            .checked_add(1)?
+            .checked_mul(height.checked_add(1)?)?
+            .checked_mul(mem::size_of::<i64>())?,
+    };
 
-impl<'a, T> Overlaps<'a, T>
+    // see `not_single_cells`
+    count_bytes.checked_add(difference_bytes)?.checked_add(summed_area_bytes(width, height)?)
+}
+
+
+// bytes of a u32 summed-area table, a row and a column larger than the grid
+fn summed_area_bytes(width: usize, height: usize) -> Option<usize> {
+    width
+        .checked_add(1)?
+        .checked_mul(height.checked_add(1)?)?
+        .checked_mul(mem::size_of::<u32>())
+}
+
+
+// origin and size of the smallest grid fitting all the rects
+fn grid_layout<T, C, I>(rects: &[Rect<C, I>], fill: FillMethod)
+                        -> Result<((C, C), usize, usize), ExceedsFabricBounds>
+    where C: Coordinate, I: ClaimID
+{
+    let exceeds_bounds = |rect: &Rect<C, I>| ExceedsFabricBounds { id: rect.id.to_string() };
+
+    for rect in rects {
+        rect.check_bounds().map_err(|_| exceeds_bounds(rect))?;
+    }
+
+    // the grid starts at the minimum corner of the claimed cells
+    let origin = Rect::bounding_box(rects)
+        .map_or((C::zero(), C::zero()), |bounds| (bounds.x, bounds.y));
+
+    // then, find the grid size required to fit in all the rects
+    let mut grid_width: usize = 0;
+    let mut grid_height: usize = 0;
+
+    for rect in rects.iter().filter(|rect| !rect.is_empty()) {
+        let (x, y) = rect.clamped_top_right();
+
+        // offset the fact that we start coords at 0
+        let width = grid_offset(x, origin.0).and_then(|x| x.checked_add(1));
+        let height = grid_offset(y, origin.1).and_then(|y| y.checked_add(1));
+
+        grid_width = max(width.ok_or_else(|| exceeds_bounds(rect))?, grid_width);
+        grid_height = max(height.ok_or_else(|| exceeds_bounds(rect))?, grid_height);
+
+        if !grid_fits::<T>(grid_width, grid_height, fill) {
+            return Err(exceeds_bounds(rect));
+        }
+    }
+
+    Ok((origin, grid_width, grid_height))
+}
+
+
+// count grid width and height of a fabric
+// along with `extra_tables` more u32 summed-area tables over the grid
+fn fabric_grid_size<T, C>(size: FabricSize<C>, fill: FillMethod, extra_tables: usize)
+                          -> Result<(usize, usize), FabricTooLarge>
+    where C: Coordinate
+{
+    let too_large = || FabricTooLarge { size: size.to_string() };
+
+    let grid_width = size.width.to_usize().ok_or_else(too_large)?;
+    let grid_height = size.height.to_usize().ok_or_else(too_large)?;
+
+    let bytes = grid_bytes::<T>(grid_width, grid_height, fill)
+        .zip(summed_area_bytes(grid_width, grid_height))
+        .and_then(|(grid, table)| grid.checked_add(table.checked_mul(extra_tables)?));
+
+    if bytes.is_some_and(|bytes| bytes <= MAX_GRID_BYTES) {
+        Ok((grid_width, grid_height))
+    } else {
+        Err(too_large())
+    }
+}
+
+
+/// Fails like `Overlaps::<T, C>::with_fabric` would, counting
+/// `extra_tables` more u32 summed-area tables over the fabric, without
+/// allocating anything.
+pub fn check_fabric_size<T, C>(size: FabricSize<C>, extra_tables: usize) -> Result<(), FabricTooLarge>
+    where C: Coordinate
+{
+    fabric_grid_size::<T, C>(size, FillMethod::Cells, extra_tables).map(|_| ())
+}
+
+
+// the fabric as a rect, to clip claims to
+fn fabric_rect<C: Coordinate, I: ClaimID>(size: FabricSize<C>) -> Rect<C, I> {
+    let zero = C::zero();
+
+    Rect { id: I::default(), x: zero, y: zero, width: size.width, height: size.height }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Add,
+    Remove,
+}
+
+
+impl<T, C, I> Overlaps<T, C, I>
 where
-    T: 'a + AddAssign + One + PrimInt + ScalarOperand
+    T: Count,
+    C: Coordinate,
+    I: ClaimID,
 {
-    pub fn new(rects: &[Rect]) -> Overlaps<T> {
-        // first, find the grid size required to fit in all the rects
-        let mut grid_width: usize = 0;
-        let mut grid_height: usize = 0;
+    pub fn new(rects: &[Rect<C, I>]) -> Result<Overlaps<T, C, I>, ExceedsFabricBounds> {
+        let (origin, grid_width, grid_height) = grid_layout::<T, C, I>(rects, FillMethod::Cells)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), None);
+        overlaps.reset_grid(origin, (grid_width, grid_height));
+
+        Ok(overlaps)
+    }
+
+    /// Count grid of a fixed size fabric starting at (0, 0).  Claims
+    /// are allowed partially or fully outside, only their cells on
+    /// the fabric are counted.
+    pub fn with_fabric(rects: &[Rect<C, I>], size: FabricSize<C>)
+                       -> Result<Overlaps<T, C, I>, FabricTooLarge> {
+        let grid_size = fabric_grid_size::<T, C>(size, FillMethod::Cells, 0)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), Some(fabric_rect(size)));
+        overlaps.reset_grid((C::zero(), C::zero()), grid_size);
+
+        Ok(overlaps)
+    }
 
-        for rect in rects {
-            let (x, y) = rect.top_right();
+    // no grid yet, see `reset_grid`
+    fn empty(rects: Vec<Rect<C, I>>, fabric: Option<Rect<C, I>>) -> Overlaps<T, C, I> {
+        Overlaps {
+            count_grid: Overlaps::<T, C, I>::make_count_grid(0, 0),
+            origin: (C::zero(), C::zero()),
+            fabric,
+            rects,
+            depth_areas: vec![],
+            overlap_cells: 0,
+        }
+    }
+
+    fn make_count_grid(width: usize, height: usize) -> Grid<T> {
+        Grid::filled(width, height, T::zero())
+    }
+
+    // (re)count all the rects on a new grid
+    fn reset_grid(&mut self, origin: (C, C), (width, height): (usize, usize)) {
+        self.count_grid = Overlaps::<T, C, I>::make_count_grid(width, height);
+        self.origin = origin;
+
+        // all cells start unclaimed
+        self.depth_areas = if width * height > 0 { vec![width * height] } else { vec![] };
+        self.overlap_cells = 0;
 
-            // offset the fact that we start coords at 0
-            grid_width = max(x as usize + 1, grid_width);
-            grid_height = max(y as usize + 1, grid_height);
+        let rects = mem::take(&mut self.rects);
+
+        for rect in &rects {
+            self.count(rect, Change::Add);
         }
 
-        let mut overlaps = Overlaps {
-            count_grid: Overlaps::make_count_grid(grid_width, grid_height),
-            rects
+        self.rects = rects;
+    }
+
+    /// Add a claim, updating the counts in place.  An inferred grid
+    /// grows to fit the claim, which recounts all the claims.  Fails
+    /// without adding the claim if a count would saturate, the grid
+    /// keeping any growth.
+    pub fn add(&mut self, rect: &Rect<C, I>) -> Result<(), AddClaimError> {
+        let fits = self.fabric.is_some() || rect.is_empty() || {
+            let (top_x, top_y) = rect.clamped_top_right();
+
+            rect.check_bounds().is_ok() &&
+                self.grid_position(rect.x, rect.y).is_some() &&
+                self.grid_position(top_x, top_y).is_some()
         };
 
-        overlaps.fill_grid(&rects);
+        if !fits {
+            // grow the grid for the claim, counting only the others
+            self.rects.push(rect.clone());
+            let layout = grid_layout::<T, C, I>(&self.rects, FillMethod::Cells);
+            self.rects.pop();
+
+            let (origin, width, height) = layout.map_err(AddClaimError::ExceedsFabricBounds)?;
+            self.reset_grid(origin, (width, height));
+        }
+
+        if let Some((columns, rows)) = self.grid_cells(rect) {
+            let max = T::max_value();
+
+            if self.count_grid.region(columns, rows).any(|&count| count == max) {
+                let max = max.to_u64().unwrap_or(u64::MAX);
+                return Err(AddClaimError::CountOverflow(CountOverflow { id: rect.id.to_string(), max }));
+            }
+        }
+
+        self.rects.push(rect.clone());
+        self.count(rect, Change::Add);
+
+        Ok(())
+    }
+
+    /// Withdraw the first claim with the given ID, updating the counts
+    /// in place.  The grid keeps its size.
+    pub fn remove(&mut self, id: &I) -> Option<Rect<C, I>> {
+        let pos = self.rects.iter().position(|rect| &rect.id == id)?;
+        let rect = self.rects.remove(pos);
+
+        self.count(&rect, Change::Remove);
 
-        overlaps
+        Some(rect)
     }
 
-    fn make_count_grid(width: usize, height: usize) -> Array2<T> {
-        Array2::<T>::zeros((width, height))
+    fn depth(count: T) -> usize {
+        count.to_usize().unwrap_or(usize::MAX)
     }
 
-    pub fn overlap_area(&self) -> usize {
+    pub fn rects(&self) -> &[Rect<C, I>] {
+        &self.rects
+    }
+
+    /// Width and height of the count grid.
+    pub fn grid_size(&self) -> (usize, usize) {
+        self.count_grid.size()
+    }
+
+    /// The fabric the count grid is limited to, if any.
+    pub fn fabric(&self) -> Option<&Rect<C, I>> {
+        self.fabric.as_ref()
+    }
+
+    /// Fabric coordinates of the first cell of the count grid.
+    pub fn grid_origin(&self) -> (C, C) {
+        self.origin
+    }
+
+    /// Count grid cell of the given fabric cell, if within the grid.
+    pub fn grid_position(&self, x: C, y: C) -> Option<(usize, usize)> {
+        let (width, height) = self.grid_size();
+
+        let column = grid_offset(x, self.origin.0).filter(|&column| column < width)?;
+        let row = grid_offset(y, self.origin.1).filter(|&row| row < height)?;
+
+        Some((column, row))
+    }
+
+    /// Number of claims covering the fabric cell.
+    pub fn depth_at(&self, x: C, y: C) -> usize {
+        self.grid_position(x, y)
+            .map_or(0, |(column, row)| self.depth_at_cell(column, row))
+    }
+
+    /// Number of claims covering the count grid cell, 0 outside of
+    /// the grid.
+    pub fn depth_at_cell(&self, column: usize, row: usize) -> usize {
         self.count_grid
-            .map(|v: &T| if *v > T::one() { 1 } else { 0 })
-            .sum()
+            .get(column, row)
+            .map_or(0, |&count| Self::depth(count))
+    }
+
+    pub fn overlap_area(&self) -> usize {
+        self.overlap_cells
+    }
+
+    /// Area of the grid (or fabric) not covered by any claim.
+    pub fn unclaimed_area(&self) -> usize {
+        self.area_with_depth_exactly(0)
+    }
+
+    /// Percentage of the grid (or fabric) covered by claims, 0 for
+    /// an empty grid.
+    pub fn utilisation(&self) -> f64 {
+        let (width, height) = self.grid_size();
+
+        if width * height == 0 {
+            return 0.0;
+        }
+
+        self.area_with_depth_at_least(1) as f64 * 100.0 / (width * height) as f64
+    }
+
+    /// Claims with cells off the fabric, in the order the rects were
+    /// given.  Claims are never outside an inferred grid.
+    pub fn claims_outside_fabric(&self) -> Vec<OutsideClaim<I>> {
+        let fabric = match &self.fabric {
+            Some(fabric) => fabric,
+            None => return vec![],
+        };
+
+        self.rects
+            .iter()
+            .filter(|rect| !rect.is_empty())
+            .filter_map(|rect| {
+                let area = rect.area().unwrap_or(u64::MAX);
+
+                let inside = rect
+                    .intersection(fabric)
+                    .and_then(|inside| inside.area())
+                    .unwrap_or(0);
+
+                if inside == area {
+                    return None;
+                }
+
+                Some(OutsideClaim {
+                    id: rect.id.clone(), cells_outside: area - inside, fully_outside: inside == 0
+                })
+            })
+            .collect()
+    }
+
+    /// Area covered by `depth` or more claims.
+    pub fn area_with_depth_at_least(&self, depth: usize) -> usize {
+        self.depth_areas.iter().skip(depth).sum()
+    }
+
+    /// Area covered by exactly `depth` claims.
+    pub fn area_with_depth_exactly(&self, depth: usize) -> usize {
+        self.depth_areas.get(depth).cloned().unwrap_or(0)
+    }
+
+    /// Area covered by each number of claims, indexed by depth, up to
+    /// the maximum depth.  Depth 0 is the unclaimed part of the grid.
+    pub fn depth_histogram(&self) -> Vec<usize> {
+        let mut histogram = self.depth_areas.clone();
+
+        // removed claims can leave the deepest entries empty
+        while histogram.len() > 1 && histogram.last() == Some(&0) {
+            histogram.pop();
+        }
+
+        histogram
+    }
+
+    /// Claims not overlapping any other, each checked in O(1) against
+    /// a summed-area table of the cells not covered by exactly one claim.
+    pub fn iter_non_overlapping_rects(&self) -> impl Iterator<Item=&Rect<C, I>> {
+        let not_single = self.not_single_cells();
+
+        self.rects
+            .iter()
+            .filter(move |rect| self.is_non_overlapping(rect, &not_single))
+    }
+
+    // summed-area table of the count grid cells with a depth other than 1
+    // (counted in `grid_bytes`, so the cells fit well within a u32)
+    fn not_single_cells(&self) -> SummedArea<u32> {
+        let (width, height) = self.grid_size();
+
+        SummedArea::new(width, height, |x, y| (self.depth_at_cell(x, y) != 1) as u32)
+    }
+
+    fn is_non_overlapping(&self, rect: &Rect<C, I>, not_single: &SummedArea<u32>) -> bool {
+        if rect.is_empty() {
+            return true;
+        }
+
+        let (top_x, top_y) = rect.clamped_top_right();
+
+        // cells off the grid (or fabric) aren't covered by the claim
+        match (self.grid_position(rect.x, rect.y), self.grid_position(top_x, top_y)) {
+            (Some((first_column, first_row)), Some((last_column, last_row))) => {
+                let (width, height) = (last_column - first_column + 1, last_row - first_row + 1);
+
+                not_single.sum(first_column, first_row, width, height) == 0
+            },
+            _ => false,
+        }
     }
 
-    pub fn iter_non_overlapping_rects(&self) -> impl Iterator<Item=&Rect> {
-        // move = take ownership of enclosing scope
-        let count = move |x, y| *self.count_grid.get((x as usize, y as usize)).unwrap();
+    /// Per-claim breakdown of contested cells, overlap depth and
+    /// conflicting claims, in the order the rects were given.
+    pub fn claim_reports(&self) -> Vec<ClaimReport<I>> {
+        let index = RectIndex::new(&self.rects);
 
         self.rects
             .iter()
-            .filter(
-                move |rect| {
-                    rect.iter_coords()
-                        .all(|(x, y)| count(x, y) == T::one())
+            .map(|rect| {
+                let mut contested_cells = 0;
+                let mut max_depth = 0;
+
+                for (x, y) in rect.iter_coords() {
+                    let depth = self.depth_at(x, y);
+
+                    if depth > 1 {
+                        contested_cells += 1;
+                    }
+
+                    max_depth = max(depth, max_depth);
                 }
-            )
+
+                let mut conflicts: Vec<_> = index
+                    .query_region(rect)
+                    .filter(|other| !ptr::eq(*other, rect))
+                    .map(|other| other.id.clone())
+                    .collect();
+
+                conflicts.sort();
+
+                ClaimReport { id: rect.id.clone(), contested_cells, max_depth, conflicts }
+            })
+            .collect()
     }
 
-    fn fill_grid(&mut self, rects: &[Rect]) -> () {
+    // count grid columns and rows covered by the rect, only cells on
+    // the fabric being counted
+    fn grid_cells(&self, rect: &Rect<C, I>) -> Option<(Range<usize>, Range<usize>)> {
+        let clipped = match &self.fabric {
+            Some(fabric) => rect.intersection(fabric),
+            None => Some(rect.clone()),
+        };
+
+        let rect = match clipped {
+            Some(ref rect) if !rect.is_empty() => rect,
+            _ => return None,
+        };
+
+        // all rects are known to fit in the grid at this point
+        let (top_x, top_y) = rect.clamped_top_right();
+
+        let grid_position = |x, y| {
+            self.grid_position(x, y).expect("rects fit in the grid")
+        };
+
+        let (first_column, first_row) = grid_position(rect.x, rect.y);
+        let (last_column, last_row) = grid_position(top_x, top_y);
+
+        Some((first_column .. last_column + 1, first_row .. last_row + 1))
+    }
+
+    fn count(&mut self, rect: &Rect<C, I>, change: Change) {
         let one = T::one();
 
-        for rect in rects {
-            if rect.is_empty() {
-                continue;
-            }
+        let (columns, rows) = match self.grid_cells(rect) {
+            Some(cells) => cells,
+            None => return,
+        };
+
+        let region = self.count_grid.region_mut(columns, rows);
+        let depth_areas = &mut self.depth_areas;
+        let overlap_cells = &mut self.overlap_cells;
+
+        // modify region in-place, avoid potential overflows
+        for count in region {
+            let before = Self::depth(*count);
+
+            *count = match change {
+                Change::Add => count.saturating_add(one),
+                Change::Remove => count.saturating_sub(one),
+            };
 
-            // s! indexer macro only accepts usize
-            let (top_x, top_y) = rect.top_right();
+            let after = Self::depth(*count);
 
-            let rect_slice = s![rect.x as usize ..= top_x as usize,
-                                rect.y as usize ..= top_y as usize];
+            if after >= depth_areas.len() {
+                depth_areas.resize(after + 1, 0);
+            }
 
-            let mut slice = self.count_grid.slice_mut(rect_slice);
+            depth_areas[before] -= 1;
+            depth_areas[after] += 1;
 
-            // modify slice in-place, avoid potential overflows
-            slice.map_mut(|v| *v = v.saturating_add(one));
+            match (before >= 2, after >= 2) {
+                (false, true) => *overlap_cells += 1,
+                (true, false) => *overlap_cells -= 1,
+                _ => {},
+            }
         }
     }
 }
@@ -94,8 +759,9 @@ where
 mod tests {
     use std::collections::HashSet;
     use crate::rect::{Rect, RectIDType};
+    use crate::report::ClaimReport;
 
-    use super::RectOverlaps;
+    use super::{check_fabric_size, grid_bytes, grid_fits, CellType, FabricSize, FillMethod, OutsideClaim, Overlaps, RectOverlaps};
 
     #[test]
     fn test_overlaps() {
@@ -105,7 +771,7 @@ mod tests {
             Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
         ];
 
-        let overlaps = RectOverlaps::new(&rects);
+        let overlaps = RectOverlaps::new(&rects).unwrap();
 
         let area = overlaps.overlap_area();
         assert_eq!(area, 4);
@@ -121,16 +787,355 @@ mod tests {
         assert_eq!(non_overlapping_ids, expected);
     }
 
+    #[test]
+    fn test_depth_areas() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+            Rect { id: 4, x: 3, y: 3, width: 1, height: 2 },
+        ];
+
+        let overlaps = RectOverlaps::new(&rects).unwrap();
+
+        // 6x6 grid from (1, 1), of which 32 cells are claimed
+        assert_eq!(overlaps.grid_origin(), (1, 1));
+        assert_eq!(overlaps.depth_histogram(), vec![4, 28, 2, 2]);
+
+        assert_eq!(overlaps.area_with_depth_at_least(0), 36);
+        assert_eq!(overlaps.area_with_depth_at_least(1), 32);
+        assert_eq!(overlaps.area_with_depth_at_least(2), 4);
+        assert_eq!(overlaps.area_with_depth_at_least(3), 2);
+        assert_eq!(overlaps.area_with_depth_at_least(4), 0);
+
+        assert_eq!(overlaps.area_with_depth_exactly(1), 28);
+        assert_eq!(overlaps.area_with_depth_exactly(3), 2);
+        assert_eq!(overlaps.area_with_depth_exactly(5), 0);
+
+        assert_eq!(overlaps.overlap_area(), 4);
+    }
+
+    #[test]
+    fn test_depth_does_not_saturate() {
+        let rects: Vec<_> = (0..300)
+            .map(|id| Rect { id, x: 0, y: 0, width: 2, height: 1 })
+            .collect();
+
+        let cell_type = CellType::for_claims(rects.len());
+        assert_eq!(cell_type, CellType::U16);
+
+        let overlaps = Overlaps::<u16>::new(&rects).unwrap();
+        assert_eq!(overlaps.area_with_depth_exactly(300), 2);
+
+        // too small cell types saturate instead
+        let overlaps = Overlaps::<u8>::new(&rects).unwrap();
+        assert_eq!(overlaps.area_with_depth_exactly(255), 2);
+    }
+
+    #[test]
+    fn test_cell_type() {
+        assert_eq!(CellType::for_claims(0), CellType::U8);
+        assert_eq!(CellType::for_claims(255), CellType::U8);
+        assert_eq!(CellType::for_claims(256), CellType::U16);
+        assert_eq!(CellType::for_claims(70000), CellType::U32);
+
+        assert_eq!("u32".parse::<CellType>().unwrap(), CellType::U32);
+        assert_eq!(format!("{}", "u64".parse::<CellType>().unwrap()), "u64");
+
+        let err = "i8".parse::<CellType>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown cell type: 'i8'");
+
+        assert!(CellType::U16.check_fits(1000).is_ok());
+
+        let err = CellType::U8.check_fits(1000).unwrap_err();
+        assert_eq!(format!("{}", err), "Cell type u8 can't count up to 1000 claims");
+    }
+
+    #[test]
+    fn test_fill_method() {
+        assert_eq!("cells".parse::<FillMethod>().unwrap(), FillMethod::Cells);
+        assert_eq!("differences".parse::<FillMethod>().unwrap(), FillMethod::Differences);
+
+        let err = "rows".parse::<FillMethod>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown fill method: 'rows'");
+    }
+
+    #[test]
+    fn test_claim_reports() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+            Rect { id: 4, x: 3, y: 3, width: 1, height: 1 },
+            Rect { id: 5, x: 3, y: 3, width: 0, height: 0 },
+        ];
+
+        let overlaps = RectOverlaps::new(&rects).unwrap();
+
+        assert_eq!(
+            overlaps.claim_reports(),
+            vec![
+                ClaimReport { id: 1, contested_cells: 4, max_depth: 3, conflicts: vec![2, 4] },
+                ClaimReport { id: 2, contested_cells: 4, max_depth: 3, conflicts: vec![1, 4] },
+                ClaimReport { id: 3, contested_cells: 0, max_depth: 1, conflicts: vec![] },
+                ClaimReport { id: 4, contested_cells: 1, max_depth: 3, conflicts: vec![1, 2] },
+                ClaimReport { id: 5, contested_cells: 0, max_depth: 0, conflicts: vec![] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negative_origin() {
+        let rects: Vec<Rect<i64, String>> = vec![
+            Rect { id: "west".into(), x: -1_000_000_000_000, y: -3, width: 4, height: 4 },
+            Rect { id: "hall".into(), x: -999_999_999_998, y: -1, width: 6, height: 1 },
+            Rect { id: "void".into(), x: 0, y: 0, width: 0, height: 0 },
+        ];
+
+        let overlaps = Overlaps::<u8, _, _>::new(&rects).unwrap();
+
+        assert_eq!(overlaps.grid_origin(), (-1_000_000_000_000, -3));
+        assert_eq!(overlaps.grid_size(), (8, 4));
+        assert_eq!(overlaps.grid_position(-999_999_999_998, -1), Some((2, 2)));
+        assert_eq!(overlaps.grid_position(0, 0), None);
+
+        assert_eq!(overlaps.depth_at(-999_999_999_997, -1), 2);
+        assert_eq!(overlaps.depth_at(-1_000_000_000_001, -1), 0);
+        assert_eq!(overlaps.overlap_area(), 2);
+
+        let reports = overlaps.claim_reports();
+        assert_eq!(reports[0].conflicts, vec![String::from("hall")]);
+
+        // too far apart to fit in a single grid
+        let rects = vec![
+            Rect { id: 1, x: i64::MIN, y: 0, width: 1, height: 1 },
+            Rect { id: 2, x: i64::MAX - 1, y: 0, width: 1, height: 1 },
+        ];
+
+        let err = Overlaps::<u8, i64>::new(&rects).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 2 exceeds fabric bounds");
+    }
+
+    #[test]
+    fn test_fabric() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+            Rect { id: 4, x: 9, y: 0, width: 2, height: 1 },
+            Rect { id: 5, x: 20, y: 20, width: 1, height: 1 },
+        ];
+
+        let overlaps = RectOverlaps::with_fabric(&rects, FabricSize { width: 10, height: 10 }).unwrap();
+
+        assert_eq!(overlaps.grid_origin(), (0, 0));
+        assert_eq!(overlaps.grid_size(), (10, 10));
+        assert_eq!(overlaps.overlap_area(), 4);
+
+        // 32 cells of the first three claims, 1 of the fourth
+        assert_eq!(overlaps.unclaimed_area(), 67);
+        assert!((overlaps.utilisation() - 33.0).abs() < 1e-9);
+
+        assert_eq!(
+            overlaps.claims_outside_fabric(),
+            vec![
+                OutsideClaim { id: 4, cells_outside: 1, fully_outside: false },
+                OutsideClaim { id: 5, cells_outside: 1, fully_outside: true },
+            ]
+        );
+
+        // an inferred grid is fully claimed or not, nothing's outside
+        let overlaps = RectOverlaps::new(&rects[..3]).unwrap();
+
+        assert!(overlaps.claims_outside_fabric().is_empty());
+        assert_eq!(overlaps.unclaimed_area(), 4);
+    }
+
+    #[test]
+    fn test_fabric_size() {
+        assert_eq!("1000x1000".parse::<FabricSize>().unwrap(), FabricSize { width: 1000, height: 1000 });
+        assert_eq!(format!("{}", FabricSize { width: 3, height: 4 }), "3x4");
+
+        let err = "1000".parse::<FabricSize>().unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid fabric size: '1000', expected WIDTHxHEIGHT");
+
+        assert!("10x-1".parse::<FabricSize<i32>>().is_err());
+        assert!("10x10x10".parse::<FabricSize>().is_err());
+
+        let err = RectOverlaps::with_fabric(&[], FabricSize { width: 1 << 20, height: 1 << 20 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Fabric of 1048576x1048576 cells is too large");
+
+        let empty = RectOverlaps::with_fabric(&[], FabricSize { width: 0, height: 0 }).unwrap();
+        assert_eq!(empty.utilisation(), 0.0);
+    }
+
+    #[test]
+    fn test_grid_bytes() {
+        // the summed-area table is a row and a column larger, of u32
+        assert_eq!(grid_bytes::<u8>(3, 2, FillMethod::Cells), Some(6 + 12 * 4));
+        assert_eq!(grid_bytes::<u64>(3, 2, FillMethod::Cells), Some(6 * 8 + 12 * 4));
+
+        // and so is the difference array, of i64
+        assert_eq!(grid_bytes::<u64>(3, 2, FillMethod::Differences), Some(6 * 8 + 12 * 8 + 12 * 4));
+
+        // right at the limit, and a row past it
+        assert!(grid_fits::<u8>(8192, 26211, FillMethod::Cells));
+        assert!(!grid_fits::<u8>(8192, 26212, FillMethod::Cells));
+        assert!(grid_fits::<u64>(8192, 10921, FillMethod::Cells));
+        assert!(!grid_fits::<u64>(8192, 10922, FillMethod::Cells));
+        assert!(grid_fits::<u64>(8192, 6552, FillMethod::Differences));
+        assert!(!grid_fits::<u64>(8192, 6553, FillMethod::Differences));
+        assert!(!grid_fits::<u8>(1 << 14, 1 << 14, FillMethod::Differences));
+
+        assert!(!grid_fits::<u8>(usize::MAX, 2, FillMethod::Cells));
+        assert!(!grid_fits::<u8>(usize::MAX, 0, FillMethod::Differences));
+
+        // failing before allocating anything
+        let err = Overlaps::<u64>::with_fabric(&[], FabricSize { width: 1 << 15, height: (1 << 12) + 1 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Fabric of 32768x4097 cells is too large");
+
+        let err = Overlaps::<u8>::diff_with_fabric(&[], FabricSize { width: 1 << 14, height: 1 << 14 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Fabric of 16384x16384 cells is too large");
+
+        let rects = [Rect { id: 1, x: 0, y: 0, width: 8192, height: 8192 }];
+        let err = Overlaps::<u64>::diff_new(&rects).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 1 exceeds fabric bounds");
+
+        // more summed-area tables leave room for fewer cells
+        assert!(check_fabric_size::<u32, u32>(FabricSize { width: 8192, height: 16382 }, 0).is_ok());
+        assert!(check_fabric_size::<u32, u32>(FabricSize { width: 8192, height: 8190 }, 2).is_ok());
+
+        let err = check_fabric_size::<u32, u32>(FabricSize { width: 8192, height: 8191 }, 2).unwrap_err();
+        assert_eq!(format!("{}", err), "Fabric of 8192x8191 cells is too large");
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+        ];
+
+        let mut overlaps = RectOverlaps::new(&rects).unwrap();
+        assert_eq!(overlaps.overlap_area(), 4);
+
+        overlaps.add(&Rect { id: 3, x: 3, y: 3, width: 1, height: 2 }).unwrap();
+
+        // deepening existing overlaps only
+        assert_eq!(overlaps.overlap_area(), 4);
+        assert_eq!(overlaps.depth_histogram(), vec![8, 24, 2, 2]);
+        assert!(overlaps.iter_non_overlapping_rects().next().is_none());
+
+        // growing the grid recounts everything
+        overlaps.add(&Rect { id: 4, x: 0, y: 0, width: 1, height: 1 }).unwrap();
+
+        assert_eq!(overlaps.grid_origin(), (0, 0));
+        assert_eq!(overlaps.grid_size(), (7, 7));
+        assert_eq!(overlaps.overlap_area(), 4);
+        assert_eq!(overlaps.area_with_depth_at_least(1), 29);
+
+        assert_eq!(overlaps.remove(&2).map(|rect| rect.id), Some(2));
+        assert_eq!(overlaps.remove(&2), None);
+
+        assert_eq!(overlaps.overlap_area(), 2);
+        assert_eq!(overlaps.depth_histogram(), vec![32, 15, 2]);
+
+        let ids: Vec<_> = overlaps.iter_non_overlapping_rects().map(|rect| rect.id).collect();
+        assert_eq!(ids, vec![4]);
+
+        let err = overlaps.add(&Rect { id: 5, x: 0, y: 0, width: 1 << 20, height: 1 << 20 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 5 exceeds fabric bounds");
+        assert_eq!(overlaps.rects().len(), 3);
+        assert_eq!(overlaps.overlap_area(), 2);
+    }
+
+    #[test]
+    fn test_add_does_not_saturate() {
+        let mut overlaps = RectOverlaps::new(&[]).unwrap();
+
+        let added = (0..300)
+            .filter(|&id| overlaps.add(&Rect { id, x: 0, y: 0, width: 2, height: 1 }).is_ok())
+            .count();
+
+        assert_eq!(added, 255);
+        assert_eq!(overlaps.area_with_depth_exactly(255), 2);
+
+        let err = overlaps.add(&Rect { id: 300, x: 1, y: 0, width: 2, height: 1 }).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 300 would take cell counts past 255");
+
+        // the grid grew for the claim, but it wasn't counted
+        assert_eq!(overlaps.grid_size(), (3, 1));
+        assert_eq!(overlaps.rects().len(), 255);
+        assert_eq!(overlaps.area_with_depth_exactly(0), 1);
+
+        // so removing claims keeps the counts exact
+        overlaps.remove(&0).unwrap();
+        assert_eq!(overlaps.area_with_depth_exactly(254), 2);
+        assert_eq!(overlaps.overlap_area(), 2);
+
+        overlaps.add(&Rect { id: 300, x: 1, y: 0, width: 2, height: 1 }).unwrap();
+        assert_eq!(overlaps.depth_histogram()[254..], [1, 1]);
+        assert_eq!(overlaps.area_with_depth_exactly(1), 1);
+    }
+
+
+    #[test]
+    fn test_matches_rebuild() {
+        let rects: Vec<_> = (0..40)
+            .map(|id| Rect { id, x: id * 7 % 13, y: id * 5 % 11, width: id % 4 + 1, height: id % 3 + 1 })
+            .collect();
+
+        let mut overlaps = RectOverlaps::new(&[]).unwrap();
+
+        for rect in &rects {
+            overlaps.add(rect).unwrap();
+        }
+
+        for id in (0..40).filter(|id| id % 3 == 0) {
+            overlaps.remove(&id).unwrap();
+        }
+
+        let remaining: Vec<_> = rects.iter().filter(|rect| rect.id % 3 != 0).cloned().collect();
+        // the grid doesn't shrink, compare on the same area
+        let (width, height) = overlaps.grid_size();
+        let fabric = FabricSize { width: width as u32, height: height as u32 };
+        let rebuilt = RectOverlaps::with_fabric(&remaining, fabric).unwrap();
+
+        assert_eq!(overlaps.grid_origin(), (0, 0));
+        assert_eq!(overlaps.depth_histogram(), rebuilt.depth_histogram());
+        assert_eq!(overlaps.overlap_area(), rebuilt.overlap_area());
+    }
+
     #[test]
     fn test_overlaps_empty() {
         let rects = vec![];
 
-        let overlaps = RectOverlaps::new(&rects);
+        let overlaps = RectOverlaps::new(&rects).unwrap();
         let area = overlaps.overlap_area();
 
         assert_eq!(area, 0);
     }
 
+    #[test]
+    fn test_overlaps_exceeding_bounds() {
+        let rects = vec![
+            Rect { id: 1, x: 1, y: 1, width: 1, height: 1 },
+            Rect { id: 2, x: 5, y: u32::MAX, width: 2, height: 2 },
+        ];
+
+        let err = RectOverlaps::new(&rects).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 2 exceeds fabric bounds");
+
+        // each fits on its own, but not both together
+        let rects = vec![
+            Rect { id: 1, x: 0, y: 0, width: 1 << 20, height: 1 },
+            Rect { id: 2, x: 0, y: 0, width: 1, height: 1 << 20 },
+        ];
+
+        let err = RectOverlaps::new(&rects).err().unwrap();
+        assert_eq!(format!("{}", err), "Claim 2 exceeds fabric bounds");
+    }
+
     #[test]
     fn test_overlaps_zero_size_rects() {
         let rects = vec![
@@ -138,7 +1143,7 @@ mod tests {
             Rect { id: 2, x: 1, y: 1, width: 0, height: 0 },
         ];
 
-        let overlaps = RectOverlaps::new(&rects);
+        let overlaps = RectOverlaps::new(&rects).unwrap();
         let area = overlaps.overlap_area();
 
         assert_eq!(area, 0);
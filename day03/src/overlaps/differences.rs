@@ -0,0 +1,143 @@
+use num_traits::NumCast;
+
+use common::grid::Grid;
+
+use crate::rect::{ClaimID, Coordinate, Rect};
+
+use super::{fabric_grid_size, fabric_rect, grid_layout, Count, ExceedsFabricBounds, FabricSize, FabricTooLarge, FillMethod, Overlaps};
+
+
+impl<T, C, I> Overlaps<T, C, I>
+where
+    T: Count,
+    C: Coordinate,
+    I: ClaimID,
+{
+    /// Same as `new`, filling the count grid with a difference array,
+    /// which is faster when claims are large compared to the grid.
+    pub fn diff_new(rects: &[Rect<C, I>]) -> Result<Overlaps<T, C, I>, ExceedsFabricBounds> {
+        let (origin, grid_width, grid_height) = grid_layout::<T, C, I>(rects, FillMethod::Differences)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), None);
+        overlaps.diff_reset_grid(origin, (grid_width, grid_height));
+
+        Ok(overlaps)
+    }
+
+    /// Same as `with_fabric`, filling the count grid with a difference
+    /// array.
+    pub fn diff_with_fabric(rects: &[Rect<C, I>], size: FabricSize<C>)
+                            -> Result<Overlaps<T, C, I>, FabricTooLarge> {
+        let grid_size = fabric_grid_size::<T, C>(size, FillMethod::Differences, 0)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), Some(fabric_rect(size)));
+        overlaps.diff_reset_grid((C::zero(), C::zero()), grid_size);
+
+        Ok(overlaps)
+    }
+
+    // difference array version of `reset_grid`: each claim adds 1 at
+    // its first cell and subtracts it just past its edges, so that
+    // the prefix sums over the array are the counts
+    fn diff_reset_grid(&mut self, origin: (C, C), (width, height): (usize, usize)) {
+        self.count_grid = Overlaps::<T, C, I>::make_count_grid(width, height);
+        self.origin = origin;
+        self.depth_areas = vec![];
+        self.overlap_cells = 0;
+
+        // one extra column and row for the claims reaching the edges
+        let mut differences = Grid::<i64>::filled(width + 1, height + 1, 0);
+
+        for (columns, rows) in self.rects.iter().filter_map(|rect| self.grid_cells(rect)) {
+            differences[(columns.start, rows.start)] += 1;
+            differences[(columns.end, rows.start)] -= 1;
+            differences[(columns.start, rows.end)] -= 1;
+            differences[(columns.end, rows.end)] += 1;
+        }
+
+        // nothing to count, and rows can't be empty
+        if width * height == 0 {
+            return;
+        }
+
+        // prefix sums of each column so far, summed along each row
+        let mut column_sums = vec![0; width];
+        let mut depth_areas = vec![];
+
+        for (row, counts) in self.count_grid.cells_mut().chunks_mut(width).enumerate() {
+            let mut depth = 0;
+
+            for ((count, column_sum), difference) in counts.iter_mut().zip(&mut column_sums).zip(differences.row(row)) {
+                *column_sum += difference;
+                depth += *column_sum;
+
+                // saturating, like `count`
+                *count = <T as NumCast>::from(depth).unwrap_or_else(T::max_value);
+
+                let count_depth = Self::depth(*count);
+
+                if count_depth >= depth_areas.len() {
+                    depth_areas.resize(count_depth + 1, 0);
+                }
+
+                depth_areas[count_depth] += 1;
+            }
+        }
+
+        self.depth_areas = depth_areas;
+        self.overlap_cells = self.area_with_depth_at_least(2);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use crate::overlaps::{Count, FabricSize, Overlaps, RectOverlaps};
+    use crate::rect::Rect;
+
+    fn assert_same<T: Count + Debug>(cells: &Overlaps<T>, differences: &Overlaps<T>) {
+        assert_eq!(differences.grid_origin(), cells.grid_origin());
+        assert_eq!(differences.count_grid, cells.count_grid);
+        assert_eq!(differences.depth_areas, cells.depth_areas);
+        assert_eq!(differences.overlap_area(), cells.overlap_area());
+    }
+
+    #[test]
+    fn test_diff_new() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+            Rect { id: 4, x: 3, y: 3, width: 1, height: 2 },
+            Rect { id: 5, x: 2, y: 2, width: 0, height: 3 },
+        ];
+
+        let overlaps = RectOverlaps::diff_new(&rects).unwrap();
+        assert_eq!(overlaps.depth_histogram(), vec![4, 28, 2, 2]);
+
+        assert_same(&RectOverlaps::new(&rects).unwrap(), &overlaps);
+        assert_same(&RectOverlaps::new(&[]).unwrap(), &RectOverlaps::diff_new(&[]).unwrap());
+
+        // saturated counts are the same too
+        let stacked: Vec<_> = (0..300).map(|id| Rect { id, x: 0, y: 0, width: 3, height: 1 }).collect();
+        assert_same(&RectOverlaps::new(&stacked).unwrap(), &RectOverlaps::diff_new(&stacked).unwrap());
+    }
+
+    #[test]
+    fn test_diff_with_fabric() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 9, y: 9, width: 2, height: 2 },
+        ];
+
+        for &(width, height) in &[(8, 8), (4, 5), (1, 1), (0, 0)] {
+            let size = FabricSize { width, height };
+
+            assert_same(&RectOverlaps::with_fabric(&rects, size).unwrap(),
+                        &RectOverlaps::diff_with_fabric(&rects, size).unwrap());
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp::{max, min};
+
+use rayon::prelude::*;
+
+use crate::rect::{ClaimID, Coordinate, Rect};
+
+use super::{fabric_grid_size, fabric_rect, grid_layout, Count, ExceedsFabricBounds, FabricSize, FabricTooLarge, FillMethod, Overlaps};
+
+
+// bands per thread, so that threads finishing early can pick up more
+const BANDS_PER_THREAD: usize = 4;
+
+
+impl<T, C, I> Overlaps<T, C, I>
+where
+    T: Count,
+    C: Coordinate,
+    I: ClaimID,
+{
+    /// Same as `new`, counting the claims on several threads.
+    pub fn par_new(rects: &[Rect<C, I>]) -> Result<Overlaps<T, C, I>, ExceedsFabricBounds> {
+        let (origin, grid_width, grid_height) = grid_layout::<T, C, I>(rects, FillMethod::Cells)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), None);
+        overlaps.par_reset_grid(origin, (grid_width, grid_height));
+
+        Ok(overlaps)
+    }
+
+    /// Same as `with_fabric`, counting the claims on several threads.
+    pub fn par_with_fabric(rects: &[Rect<C, I>], size: FabricSize<C>)
+                           -> Result<Overlaps<T, C, I>, FabricTooLarge> {
+        let grid_size = fabric_grid_size::<T, C>(size, FillMethod::Cells, 0)?;
+
+        let mut overlaps = Overlaps::empty(rects.to_vec(), Some(fabric_rect(size)));
+        overlaps.par_reset_grid((C::zero(), C::zero()), grid_size);
+
+        Ok(overlaps)
+    }
+
+    // parallel version of `reset_grid`: the grid is split into bands of
+    // rows, each band counting the parts of all the claims falling into
+    // it, so no cell is shared between threads
+    fn par_reset_grid(&mut self, origin: (C, C), (width, height): (usize, usize)) {
+        self.count_grid = Overlaps::<T, C, I>::make_count_grid(width, height);
+        self.origin = origin;
+        self.depth_areas = vec![];
+        self.overlap_cells = 0;
+
+        // nothing to count, and chunks can't be empty
+        if width * height == 0 {
+            return;
+        }
+
+        let cells: Vec<_> = self.rects
+            .iter()
+            .filter_map(|rect| self.grid_cells(rect))
+            .collect();
+
+        let band_height = max(height.div_ceil(rayon::current_num_threads() * BANDS_PER_THREAD), 1);
+
+        self.count_grid
+            .cells_mut()
+            .par_chunks_mut(width * band_height)
+            .enumerate()
+            .for_each(|(band, counts)| {
+                let first_row = band * band_height;
+                let end_row = first_row + counts.len() / width;
+
+                for (columns, rows) in &cells {
+                    for row in max(rows.start, first_row)..min(rows.end, end_row) {
+                        let band_row = &mut counts[(row - first_row) * width..][..width];
+
+                        // saturating, like `count`, which makes the order irrelevant
+                        for count in &mut band_row[columns.clone()] {
+                            *count = count.saturating_add(T::one());
+                        }
+                    }
+                }
+            });
+
+        self.depth_areas = self.par_depth_areas();
+        self.overlap_cells = self.area_with_depth_at_least(2);
+    }
+
+    // area covered by each number of claims, up to the maximum depth
+    fn par_depth_areas(&self) -> Vec<usize> {
+        self.count_grid
+            .cells()
+            .par_iter()
+            .fold(Vec::new, |mut depth_areas, &count| {
+                let depth = Self::depth(count);
+
+                if depth >= depth_areas.len() {
+                    depth_areas.resize(depth + 1, 0);
+                }
+
+                depth_areas[depth] += 1;
+                depth_areas
+            })
+            .reduce(Vec::new, |mut left, right| {
+                if right.len() > left.len() {
+                    left.resize(right.len(), 0);
+                }
+
+                for (area, other) in left.iter_mut().zip(right) {
+                    *area += other;
+                }
+
+                left
+            })
+    }
+
+    /// Same as `iter_non_overlapping_rects`, checking the claims on
+    /// several threads.
+    pub fn par_non_overlapping_rects(&self) -> Vec<&Rect<C, I>> {
+        let not_single = self.not_single_cells();
+
+        self.rects
+            .par_iter()
+            .filter(|rect| self.is_non_overlapping(rect, &not_single))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use crate::overlaps::{Count, FabricSize, Overlaps, RectOverlaps};
+    use crate::rect::Rect;
+
+    // claims spread over a 300x200 fabric, many of them overlapping
+    fn rects() -> Vec<Rect> {
+        let mut seed: u32 = 1;
+
+        let mut next = |limit: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) % limit
+        };
+
+        (1..=500)
+            .map(|id| Rect { id, x: next(290), y: next(190), width: next(20), height: next(20) })
+            .collect()
+    }
+
+    fn assert_same<T: Count + Debug>(sequential: &Overlaps<T>, parallel: &Overlaps<T>) {
+        assert_eq!(parallel.grid_origin(), sequential.grid_origin());
+        assert_eq!(parallel.count_grid, sequential.count_grid);
+        assert_eq!(parallel.depth_areas, sequential.depth_areas);
+        assert_eq!(parallel.overlap_area(), sequential.overlap_area());
+
+        let sequential_ids: Vec<_> = sequential.iter_non_overlapping_rects().map(|rect| rect.id).collect();
+        let parallel_ids: Vec<_> = parallel.par_non_overlapping_rects().iter().map(|rect| rect.id).collect();
+        assert_eq!(parallel_ids, sequential_ids);
+    }
+
+    #[test]
+    fn test_par_new() {
+        let rects = rects();
+
+        assert_same(&RectOverlaps::new(&rects).unwrap(), &RectOverlaps::par_new(&rects).unwrap());
+
+        // saturated counts are the same too
+        let stacked: Vec<_> = (0..300).map(|id| Rect { id, x: 0, y: 0, width: 3, height: 1 }).collect();
+        assert_same(&RectOverlaps::new(&stacked).unwrap(), &RectOverlaps::par_new(&stacked).unwrap());
+
+        assert_same(&RectOverlaps::new(&[]).unwrap(), &RectOverlaps::par_new(&[]).unwrap());
+    }
+
+    #[test]
+    fn test_par_with_fabric() {
+        let rects = rects();
+
+        // claims partly outside are clipped the same way
+        for &(width, height) in &[(300, 200), (150, 100), (1, 1), (0, 0)] {
+            let size = FabricSize { width, height };
+
+            assert_same(&Overlaps::<u16>::with_fabric(&rects, size).unwrap(),
+                        &Overlaps::<u16>::par_with_fabric(&rects, size).unwrap());
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::fmt;
-use std::num::ParseIntError;
+use std::hash::Hash;
+use std::iter;
 use std::str::FromStr;
 
 use itertools::iproduct;
-use lazy_static::lazy_static;
-use regex::Regex;
+use num_traits::int::PrimInt;
 
 use common::Fail;
 
@@ -13,93 +14,622 @@ use common::Fail;
 pub type RectIDType = u32;
 
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Rect {
-    pub id: RectIDType,
-    pub x: u32,     // x, y : bottom left corner of the rect
-    pub y: u32,
-    pub width: u32,
-    pub height: u32,
+/// With the `serde` feature, types which can be serialised and
+/// deserialised, otherwise any type.
+#[cfg(feature = "serde")]
+pub trait MaybeSerde: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(feature = "serde")]
+impl<T> MaybeSerde for T where T: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(not(feature = "serde"))]
+pub trait MaybeSerde {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> MaybeSerde for T {}
+
+
+/// Integer types usable as rect coordinates and sizes, signed
+/// coordinates allowing rects left of or below the origin.
+pub trait Coordinate: PrimInt + Hash + fmt::Debug + fmt::Display + FromStr + Send + Sync + MaybeSerde {}
+
+impl<C> Coordinate for C
+    where C: PrimInt + Hash + fmt::Debug + fmt::Display + FromStr + Send + Sync + MaybeSerde {}
+
+
+/// Types usable as rect IDs.
+pub trait ClaimID: Clone + Default + Ord + Hash + fmt::Debug + fmt::Display + FromStr + Send + Sync + MaybeSerde {
+    // whether the ID is written as a number (rather than a string) in JSON
+    const NUMERIC: bool;
+}
+
+macro_rules! numeric_claim_ids {
+    ($($type:ty),*) => {
+        $(impl ClaimID for $type { const NUMERIC: bool = true; })*
+    };
+}
+
+numeric_claim_ids!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl ClaimID for String {
+    const NUMERIC: bool = false;
+}
+
+
+/// Coordinate types selectable at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateType {
+    U32,
+    U64,
+    I32,
+    I64,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Unknown coordinate type: '{}'", coordinate_type)]
+pub struct UnknownCoordinateType {
+    coordinate_type: String,
 }
 
 
-impl Rect {
-    pub fn top_right(&self) -> (u32, u32) {
-        // assuming the coordinate system starts at (0, 0)
-        // and 0 sized rects are allowed
-        let top_x = (self.x + self.width).saturating_sub(min(1, self.width));
-        let top_y = (self.y + self.height).saturating_sub(min(1, self.height));
+impl FromStr for CoordinateType {
+    type Err = UnknownCoordinateType;
 
-        (top_x, top_y)
+    fn from_str(s: &str) -> Result<CoordinateType, UnknownCoordinateType> {
+        match s {
+            "u32" => Ok(CoordinateType::U32),
+            "u64" => Ok(CoordinateType::U64),
+            "i32" => Ok(CoordinateType::I32),
+            "i64" => Ok(CoordinateType::I64),
+            _ => Err(UnknownCoordinateType { coordinate_type: s.into() }),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect<C = u32, I = RectIDType> {
+    pub id: I,
+    pub x: C,     // x, y : bottom left corner of the rect
+    pub y: C,
+    pub width: C,
+    pub height: C,
+}
+
+
+// IDs are kept as strings, so that errors don't depend on the ID type
+#[derive(Debug, Clone, PartialEq, Eq, Fail)]
+pub enum InvalidRect {
+    #[fail(display = "Rect {} exceeds the coordinate space: {} + {} overflows", id, position, size)]
+    Overflow {
+        id: String,
+        position: RectField,
+        size: RectField,
+    },
+
+    #[fail(display = "Rect {} has a negative {}", id, size)]
+    NegativeSize {
+        id: String,
+        size: RectField,
+    },
+}
+
+
+// distances are computed on wide integers, so that they can't
+// overflow whatever the coordinate type (the largest 128 bits
+// coordinates saturate)
+pub(crate) fn wide<C: Coordinate>(coord: C) -> i128 {
+    coord.to_i128().unwrap_or(i128::MAX)
+}
+
+
+// inclusive range over any coordinate type
+fn range_inclusive<C: Coordinate>(start: C, end: C) -> impl Iterator<Item=C> + Clone {
+    iter::successors(Some(start), move |&coord| {
+        if coord < end { Some(coord + C::one()) } else { None }
+    })
+        .take_while(move |&coord| coord <= end)
+}
+
+
+impl<C: Coordinate, I: ClaimID> Rect<C, I> {
+    /// Rect whose cells all fit in the coordinate space.  Rects built
+    /// directly from fields aren't checked, see `check_bounds`.
+    pub fn new(id: I, x: C, y: C, width: C, height: C) -> Result<Rect<C, I>, InvalidRect> {
+        let rect = Rect { id, x, y, width, height };
+
+        rect.check_bounds()?;
+
+        Ok(rect)
+    }
+
+    pub fn check_bounds(&self) -> Result<(), InvalidRect> {
+        let sizes = [
+            (self.x, self.width, RectField::X, RectField::Width),
+            (self.y, self.height, RectField::Y, RectField::Height),
+        ];
+
+        for &(position, size, position_field, size_field) in sizes.iter() {
+            if size < C::zero() {
+                return Err(InvalidRect::NegativeSize {
+                    id: self.id.to_string(), size: size_field
+                });
+            }
+
+            if position.checked_add(&size).is_none() {
+                return Err(InvalidRect::Overflow {
+                    id: self.id.to_string(), position: position_field, size: size_field
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn top_right(&self) -> Result<(C, C), InvalidRect> {
+        self.check_bounds()?;
+
+        Ok(self.clamped_top_right())
+    }
+
+    // like `top_right`, but cells past the end of the coordinate
+    // space are ignored, so that geometry never overflows
+    pub(crate) fn clamped_top_right(&self) -> (C, C) {
+        // 0 sized rects are allowed (and negative sizes treated as 0)
+        let top = |start: C, size: C| {
+            if size <= C::zero() {
+                start
+            } else {
+                start.checked_add(&(size - C::one())).unwrap_or_else(C::max_value)
+            }
+        };
+
+        (top(self.x, self.width), top(self.y, self.height))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.width == 0 || self.height == 0
+        self.width <= C::zero() || self.height <= C::zero()
+    }
+
+    pub fn intersects(&self, other: &Rect<C, I>) -> bool {
+        // empty rects cover no cells, so they can't share any
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+
+        let (self_top_x, self_top_y) = self.clamped_top_right();
+        let (other_top_x, other_top_y) = other.clamped_top_right();
+
+        self.x <= other_top_x && other.x <= self_top_x &&
+            self.y <= other_top_y && other.y <= self_top_y
+    }
+
+    /// Cells shared with `other`, keeping this rect's ID.
+    pub fn intersection(&self, other: &Rect<C, I>) -> Option<Rect<C, I>> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        let (self_top_x, self_top_y) = self.clamped_top_right();
+        let (other_top_x, other_top_y) = other.clamped_top_right();
+
+        let x = max(self.x, other.x);
+        let y = max(self.y, other.y);
+
+        Some(Rect {
+            id: self.id.clone(),
+            x, y,
+            width: min(self_top_x, other_top_x) - x + C::one(),
+            height: min(self_top_y, other_top_y) - y + C::one(),
+        })
+    }
+
+    pub fn contains_point(&self, x: C, y: C) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let (top_x, top_y) = self.clamped_top_right();
+
+        self.x <= x && x <= top_x && self.y <= y && y <= top_y
+    }
+
+    /// Whether all cells of `other` are also cells of this rect
+    /// (trivially true for empty rects).
+    pub fn contains_rect(&self, other: &Rect<C, I>) -> bool {
+        if other.is_empty() {
+            return true;
+        }
+
+        let (other_top_x, other_top_y) = other.clamped_top_right();
+
+        self.contains_point(other.x, other.y) && self.contains_point(other_top_x, other_top_y)
+    }
+
+    /// Smallest rect covering all cells of the given rects, with a
+    /// default ID.  `None` if none of the rects covers any cells.
+    pub fn bounding_box<'a>(rects: impl IntoIterator<Item=&'a Rect<C, I>>) -> Option<Rect<C, I>>
+        where C: 'a, I: 'a
+    {
+        let mut bounds = None;
+
+        for rect in rects {
+            if rect.is_empty() {
+                continue;
+            }
+
+            let (top_x, top_y) = rect.clamped_top_right();
+
+            bounds = match bounds {
+                None => Some((rect.x, rect.y, top_x, top_y)),
+                Some((min_x, min_y, max_x, max_y)) => Some((
+                    min(min_x, rect.x), min(min_y, rect.y),
+                    max(max_x, top_x), max(max_y, top_y),
+                )),
+            };
+        }
+
+        // a box spanning the whole coordinate space can't be represented
+        let size = |low: C, high: C| {
+            high.checked_sub(&low).map_or(C::max_value(), |span| span.saturating_add(C::one()))
+        };
+
+        bounds.map(|(min_x, min_y, max_x, max_y)| Rect {
+            id: I::default(),
+            x: min_x,
+            y: min_y,
+            width: size(min_x, max_x),
+            height: size(min_y, max_y),
+        })
+    }
+
+    /// Number of cells covered, `None` on overflow (or negative size).
+    pub fn area(&self) -> Option<u64> {
+        self.width.to_u64()?.checked_mul(self.height.to_u64()?)
+    }
+
+    /// Cells not shared with `other`, as at most four disjoint rects
+    /// keeping this rect's ID.
+    pub fn subtract(&self, other: &Rect<C, I>) -> Vec<Rect<C, I>> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        let common = match self.intersection(other) {
+            Some(common) => common,
+            None => return vec![self.clone()],
+        };
+
+        let (top_x, top_y) = self.clamped_top_right();
+        let (common_top_x, common_top_y) = common.clamped_top_right();
+
+        let one = C::one();
+        let piece = |x, y, width, height| Rect { id: self.id.clone(), x, y, width, height };
+
+        let mut remainder = vec![];
+
+        // full width strips below and above the common part
+        if common.y > self.y {
+            remainder.push(piece(self.x, self.y, self.width, common.y - self.y));
+        }
+
+        if common_top_y < top_y {
+            remainder.push(piece(self.x, common_top_y + one, self.width, top_y - common_top_y));
+        }
+
+        // left and right of the common part, within its rows
+        if common.x > self.x {
+            remainder.push(piece(self.x, common.y, common.x - self.x, common.height));
+        }
+
+        if common_top_x < top_x {
+            remainder.push(piece(common_top_x + one, common.y, top_x - common_top_x, common.height));
+        }
+
+        remainder
     }
 
-    pub fn iter_coords(&self) -> impl Iterator<Item=(u32, u32)> {
-        let (max_x, max_y) = self.top_right();
+    pub fn iter_coords(&self) -> impl Iterator<Item=(C, C)> {
+        let (max_x, max_y) = self.clamped_top_right();
         let empty = self.is_empty();
 
         // iter over x, then over y
         // e.g. (0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)
-        iproduct!(self.y ..= max_y, self.x ..= max_x)
+        iproduct!(range_inclusive(self.y, max_y), range_inclusive(self.x, max_x))
             .filter(move |(_y, _x)| !empty)
             .map(|(y, x)| (x, y))
     }
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RectField {
+    Id,
+    X,
+    Y,
+    Width,
+    Height,
+}
+
+
+impl fmt::Display for RectField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            RectField::Id => "id",
+            RectField::X => "x",
+            RectField::Y => "y",
+            RectField::Width => "width",
+            RectField::Height => "height",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    // whitespace allowed around every part of the claim
+    Lenient,
+    // exactly the format produced by `Display`
+    Strict,
+}
+
+
+// offsets are in characters, counted from 0
 #[derive(Debug, Fail)]
 pub enum ParseRectError {
-    #[fail(display = "Error parsing Rect from string: '{}'", string)]
-    MalformedString {
-        string: String
+    #[fail(display = "Error parsing Rect from string '{}': expected {} at offset {}",
+           string, expected, offset)]
+    Unexpected {
+        string: String,
+        expected: String,
+        offset: usize,
     },
 
-    #[fail(display = "Error parsing Rect: integer overflow")]
-    ParseInt(#[cause] ParseIntError)
+    #[fail(display = "Error parsing Rect from string '{}': {} overflows at offset {}",
+           string, field, offset)]
+    Overflow {
+        string: String,
+        field: RectField,
+        offset: usize,
+    },
+
+    #[fail(display = "Error parsing Rect from string '{}': invalid id at offset {}",
+           string, offset)]
+    InvalidID {
+        string: String,
+        offset: usize,
+    },
+
+    #[fail(display = "Error parsing Rect from string '{}': {} + {} exceeds the coordinate space",
+           string, position, size)]
+    OutOfBounds {
+        string: String,
+        position: RectField,
+        size: RectField,
+    },
+
+    #[fail(display = "Error parsing Rect from string '{}': negative {}", string, size)]
+    NegativeSize {
+        string: String,
+        size: RectField,
+    },
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Duplicate claim ID {} on lines {} and {}", id, first_line, second_line)]
+pub struct DuplicateClaimID {
+    pub id: String,
+    pub first_line: usize,
+    pub second_line: usize,
 }
 
 
-impl From<ParseIntError> for ParseRectError {
-    fn from(error: ParseIntError) -> Self {
-        ParseRectError::ParseInt(error)
+/// Make sure no two rects share an ID, assuming one rect per line.
+pub fn check_unique_ids<C, I>(rects: &[Rect<C, I>]) -> Result<(), DuplicateClaimID>
+    where I: ClaimID
+{
+    let mut lines = HashMap::new();
+
+    for (line, rect) in (1..).zip(rects) {
+        if let Some(&first_line) = lines.get(&rect.id) {
+            return Err(DuplicateClaimID {
+                id: rect.id.to_string(), first_line, second_line: line
+            });
+        }
+
+        lines.insert(&rect.id, line);
     }
+
+    Ok(())
 }
 
 
-impl FromStr for Rect {
-    type Err = ParseRectError;
+enum Token {
+    Literal(&'static str),
+    Id,
+    Number(RectField),
+}
+
+
+// the claim format, as produced by `Display`, e.g. "#1 @ 1,5: 9x100"
+const CLAIM_FORMAT: [Token; 10] = [
+    Token::Literal("#"), Token::Id,
+    Token::Literal(" @ "), Token::Number(RectField::X),
+    Token::Literal(","), Token::Number(RectField::Y),
+    Token::Literal(": "), Token::Number(RectField::Width),
+    Token::Literal("x"), Token::Number(RectField::Height),
+];
+
+
+struct Parser<'s> {
+    string: &'s str,
+    // in bytes
+    pos: usize,
+    mode: ParseMode,
+}
+
 
-    fn from_str(s: &str) -> Result<Rect, ParseRectError> {
-        lazy_static! {
-            static ref PATTERN: Regex = Regex::new(
-                r"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<width>\d+)x(?P<height>\d+)"
-            ).expect("Invalid regex pattern");
+impl<'s> Parser<'s> {
+    fn offset(&self) -> usize {
+        self.string[..self.pos].chars().count()
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.string[self.pos..]
+    }
+
+    fn unexpected(&self, expected: String) -> ParseRectError {
+        ParseRectError::Unexpected {
+            string: self.string.into(), expected, offset: self.offset()
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        if self.mode == ParseMode::Lenient {
+            let rest = self.rest();
+            self.pos += rest.len() - rest.trim_start().len();
         }
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), ParseRectError> {
+        // lenient literals are matched without their spaces
+        let literal = match self.mode {
+            ParseMode::Lenient => literal.trim(),
+            ParseMode::Strict => literal,
+        };
 
-        match &PATTERN.captures(s) {
-            // whole pattern + groups == 6 matches
-            Some(caps) if caps.len() == 6 => {
-                let extract = |name: &str| caps[name].parse();
+        self.skip_whitespace();
+
+        if self.rest().starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.unexpected(format!("'{}'", literal)))
+        }
+    }
 
-                let rect = Rect {
-                    id: extract("id")?, x: extract("x")?, y: extract("y")?,
-                    width: extract("width")?, height: extract("height")?,
-                };
+    fn id<I: ClaimID>(&mut self) -> Result<I, ParseRectError> {
+        self.skip_whitespace();
 
-                Ok(rect)
+        // anything up to the '@' separator
+        let rest = self.rest();
+        let len = rest.find(|ch: char| ch.is_whitespace() || ch == '@').unwrap_or(rest.len());
+        let token = &rest[..len];
+
+        if token.is_empty() {
+            return Err(self.unexpected(RectField::Id.to_string()));
+        }
+
+        let id = token.parse().map_err(|_| {
+            // numeric IDs made of digits only can't be parsed when too large
+            if I::NUMERIC && token.chars().all(|ch| ch.is_ascii_digit()) {
+                ParseRectError::Overflow {
+                    string: self.string.into(), field: RectField::Id, offset: self.offset()
+                }
+            } else {
+                ParseRectError::InvalidID { string: self.string.into(), offset: self.offset() }
             }
-            _ => {
-                Err(ParseRectError::MalformedString { string: s.into() }.into())
+        })?;
+
+        self.pos += len;
+
+        Ok(id)
+    }
+
+    fn number<C: Coordinate>(&mut self, field: RectField) -> Result<C, ParseRectError> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+
+        // only signed coordinates can be negative
+        let sign = if C::min_value() < C::zero() && rest.starts_with('-') { 1 } else { 0 };
+
+        let unsigned = &rest[sign..];
+        let digits = unsigned.len() - unsigned.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+
+        if digits == 0 {
+            return Err(self.unexpected(field.to_string()));
+        }
+
+        // only digits, so failing to parse means overflow
+        let number = rest[..sign + digits].parse().map_err(|_| ParseRectError::Overflow {
+            string: self.string.into(), field, offset: self.offset()
+        })?;
+
+        self.pos += sign + digits;
+
+        Ok(number)
+    }
+
+    fn end(&mut self) -> Result<(), ParseRectError> {
+        self.skip_whitespace();
+
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.unexpected("end of string".into()))
+        }
+    }
+}
+
+
+impl<C: Coordinate, I: ClaimID> Rect<C, I> {
+    pub fn parse(s: &str, mode: ParseMode) -> Result<Rect<C, I>, ParseRectError> {
+        let mut parser = Parser { string: s, pos: 0, mode };
+
+        let zero = C::zero();
+        let mut rect = Rect { id: I::default(), x: zero, y: zero, width: zero, height: zero };
+
+        for token in CLAIM_FORMAT.iter() {
+            match token {
+                Token::Literal(literal) => parser.literal(literal)?,
+                Token::Id => rect.id = parser.id()?,
+                Token::Number(field) => {
+                    let number = parser.number(*field)?;
+
+                    match field {
+                        RectField::X => rect.x = number,
+                        RectField::Y => rect.y = number,
+                        RectField::Width => rect.width = number,
+                        RectField::Height => rect.height = number,
+                        RectField::Id => unreachable!("IDs aren't numbers"),
+                    }
+                },
             }
         }
+
+        parser.end()?;
+
+        // all cells need to be addressable
+        rect.check_bounds().map_err(|invalid| match invalid {
+            InvalidRect::Overflow { position, size, .. } => {
+                ParseRectError::OutOfBounds { string: s.into(), position, size }
+            },
+            InvalidRect::NegativeSize { size, .. } => {
+                ParseRectError::NegativeSize { string: s.into(), size }
+            },
+        })?;
+
+        Ok(rect)
+    }
+}
+
+
+impl<C: Coordinate, I: ClaimID> FromStr for Rect<C, I> {
+    type Err = ParseRectError;
+
+    fn from_str(s: &str) -> Result<Rect<C, I>, ParseRectError> {
+        Rect::parse(s, ParseMode::Lenient)
     }
 }
 
 
-impl fmt::Display for Rect {
+impl<C: fmt::Display, I: fmt::Display> fmt::Display for Rect<C, I> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "#{} @ {},{}: {}x{}", self.id, self.x, self.y, self.width, self.height)
     }
@@ -108,24 +638,79 @@ impl fmt::Display for Rect {
 
 #[cfg(test)]
 mod tests {
-    use super::{Rect};
+    use std::collections::HashSet;
+
+    use proptest::prelude::*;
+
+    use super::{check_unique_ids, CoordinateType, InvalidRect, ParseMode, Rect, RectField};
 
     #[test]
     fn test_top_right() {
         let rect = Rect { id: 123, x: 3, y: 2, width: 5, height: 4 };
 
-        assert_eq!(rect.top_right(), (7, 5));
+        assert_eq!(rect.top_right().unwrap(), (7, 5));
     }
 
     #[test]
     fn test_top_right_size_zero() {
         let rect_at_0_0 = Rect { id: 1, x: 0, y: 0, width: 0, height: 0 };
 
-        assert_eq!(rect_at_0_0.top_right(), (0, 0));
+        assert_eq!(rect_at_0_0.top_right().unwrap(), (0, 0));
 
         let rect_at_10_10 = Rect { id: 1, x: 10, y: 10, width: 0, height: 0 };
 
-        assert_eq!(rect_at_10_10.top_right(), (10, 10));
+        assert_eq!(rect_at_10_10.top_right().unwrap(), (10, 10));
+    }
+
+    #[test]
+    fn test_top_right_overflow() {
+        let max = u32::MAX;
+
+        let rect = Rect { id: 1, x: max - 3, y: 0, width: 3, height: 1 };
+        assert_eq!(rect.top_right().unwrap(), (max - 1, 0));
+
+        let rect = Rect { id: 2, x: max - 3, y: 0, width: 4, height: 1 };
+
+        assert_eq!(
+            rect.top_right().unwrap_err(),
+            InvalidRect::Overflow { id: "2".into(), position: RectField::X, size: RectField::Width }
+        );
+
+        let rect = Rect { id: 3, x: 0, y: max, width: 1, height: max };
+        let err = rect.top_right().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Rect 3 exceeds the coordinate space: y + height overflows"
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            Rect::new(1, 3, 2, 5, 4).unwrap(),
+            Rect { id: 1, x: 3, y: 2, width: 5, height: 4 }
+        );
+
+        assert!(Rect::new(1, u32::MAX, 2, 0, 4).is_ok());
+        assert!(Rect::new(1, u32::MAX, 2, 1, 4).is_err());
+        assert!(Rect::new(1, 2, 1, 4, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_geometry_ignores_cells_past_the_coordinate_space() {
+        let max = u32::MAX;
+        let overflowing = Rect { id: 1, x: max - 1, y: 0, width: 4, height: 1 };
+
+        let coords: Vec<_> = overflowing.iter_coords().collect();
+        assert_eq!(coords, vec![(max - 1, 0), (max, 0)]);
+
+        let wide = Rect { id: 2, x: 0, y: 0, width: max, height: 1 };
+
+        assert_eq!(
+            Rect::bounding_box(&[wide, overflowing]),
+            Some(Rect { id: 0, x: 0, y: 0, width: max, height: 1 })
+        );
     }
 
     #[test]
@@ -149,6 +734,192 @@ mod tests {
         assert!(coords.is_empty());
     }
 
+    #[test]
+    fn test_intersects() {
+        let rect = Rect { id: 1, x: 1, y: 3, width: 4, height: 4 };
+
+        // overlapping, touching corner cell, adjacent, disjoint
+        assert!(rect.intersects(&Rect { id: 2, x: 3, y: 1, width: 4, height: 4 }));
+        assert!(rect.intersects(&Rect { id: 3, x: 4, y: 6, width: 2, height: 2 }));
+        assert!(!rect.intersects(&Rect { id: 4, x: 5, y: 3, width: 2, height: 2 }));
+        assert!(!rect.intersects(&Rect { id: 5, x: 10, y: 10, width: 1, height: 1 }));
+
+        // intersection is symmetrical and includes the rect itself
+        assert!(Rect { id: 2, x: 3, y: 1, width: 4, height: 4 }.intersects(&rect));
+        assert!(rect.intersects(&rect));
+    }
+
+    #[test]
+    fn test_intersects_size_zero() {
+        let rect = Rect { id: 1, x: 1, y: 1, width: 1, height: 1 };
+        let empty = Rect { id: 2, x: 1, y: 1, width: 0, height: 0 };
+
+        assert!(!rect.intersects(&empty));
+        assert!(!empty.intersects(&rect));
+        assert!(!empty.intersects(&empty));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let rect = Rect { id: 1, x: 1, y: 3, width: 4, height: 4 };
+        let other = Rect { id: 2, x: 3, y: 1, width: 4, height: 4 };
+
+        assert_eq!(rect.intersection(&other), Some(Rect { id: 1, x: 3, y: 3, width: 2, height: 2 }));
+        assert_eq!(other.intersection(&rect), Some(Rect { id: 2, x: 3, y: 3, width: 2, height: 2 }));
+
+        let far_away = Rect { id: 3, x: 50, y: 50, width: 4, height: 4 };
+        assert_eq!(rect.intersection(&far_away), None);
+
+        let empty = Rect { id: 4, x: 2, y: 4, width: 0, height: 0 };
+        assert_eq!(rect.intersection(&empty), None);
+    }
+
+    #[test]
+    fn test_contains() {
+        let rect = Rect { id: 1, x: 1, y: 3, width: 4, height: 4 };
+
+        assert!(rect.contains_point(1, 3));
+        assert!(rect.contains_point(4, 6));
+        assert!(!rect.contains_point(5, 6));
+        assert!(!rect.contains_point(0, 3));
+
+        assert!(rect.contains_rect(&rect));
+        assert!(rect.contains_rect(&Rect { id: 2, x: 2, y: 4, width: 3, height: 3 }));
+        assert!(!rect.contains_rect(&Rect { id: 2, x: 2, y: 4, width: 4, height: 3 }));
+
+        let empty = Rect { id: 3, x: 1, y: 3, width: 0, height: 0 };
+
+        assert!(!empty.contains_point(1, 3));
+        assert!(rect.contains_rect(&empty));
+        assert!(empty.contains_rect(&empty));
+        assert!(!empty.contains_rect(&rect));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let rects = [
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 0, y: 0, width: 0, height: 0 },
+        ];
+
+        assert_eq!(
+            Rect::bounding_box(&rects),
+            Some(Rect { id: 0, x: 1, y: 1, width: 6, height: 6 })
+        );
+
+        assert_eq!(Rect::bounding_box(&rects[2..]), None);
+        assert_eq!(Rect::<u32>::bounding_box(&[]), None);
+    }
+
+    #[test]
+    fn test_area() {
+        assert_eq!(Rect { id: 1, x: 1, y: 3, width: 4, height: 5 }.area(), Some(20));
+        assert_eq!(Rect { id: 1, x: 1, y: 3, width: 0, height: 5 }.area(), Some(0));
+
+        let huge = Rect { id: 1, x: 0, y: 0, width: u32::MAX, height: u32::MAX };
+        assert_eq!(huge.area(), Some(u64::from(u32::MAX) * u64::from(u32::MAX)));
+    }
+
+    #[test]
+    fn test_subtract() {
+        let rect = Rect { id: 1, x: 0, y: 0, width: 5, height: 5 };
+
+        // hole in the middle, four pieces around it
+        let hole = Rect { id: 2, x: 1, y: 2, width: 2, height: 2 };
+
+        assert_eq!(
+            rect.subtract(&hole),
+            vec![
+                Rect { id: 1, x: 0, y: 0, width: 5, height: 2 },
+                Rect { id: 1, x: 0, y: 4, width: 5, height: 1 },
+                Rect { id: 1, x: 0, y: 2, width: 1, height: 2 },
+                Rect { id: 1, x: 3, y: 2, width: 2, height: 2 },
+            ]
+        );
+
+        assert_eq!(rect.subtract(&rect), vec![]);
+        assert_eq!(rect.subtract(&Rect { id: 2, x: 9, y: 9, width: 1, height: 1 }), vec![rect.clone()]);
+        assert_eq!(rect.subtract(&Rect { id: 2, x: 1, y: 1, width: 0, height: 0 }), vec![rect.clone()]);
+        assert_eq!(Rect { id: 3, x: 1, y: 1, width: 0, height: 4 }.subtract(&rect), vec![]);
+    }
+
+    fn coords(rect: &Rect) -> HashSet<(u32, u32)> {
+        rect.iter_coords().collect()
+    }
+
+    // small rects, so that they overlap often, including empty ones
+    fn small_rect() -> impl Strategy<Value=Rect> {
+        (0..12u32, 0..12u32, 0..8u32, 0..8u32)
+            .prop_map(|(x, y, width, height)| Rect { id: 1, x, y, width, height })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_intersection_matches_coords(rect in small_rect(), other in small_rect()) {
+            let expected: HashSet<_> = coords(&rect).intersection(&coords(&other)).cloned().collect();
+            let actual = rect.intersection(&other).map(|common| coords(&common)).unwrap_or_default();
+
+            prop_assert_eq!(rect.intersects(&other), !expected.is_empty());
+            prop_assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn prop_containment_matches_coords(rect in small_rect(), other in small_rect(),
+                                           x in 0..20u32, y in 0..20u32) {
+            prop_assert_eq!(rect.contains_point(x, y), coords(&rect).contains(&(x, y)));
+            prop_assert_eq!(rect.contains_rect(&other), coords(&other).is_subset(&coords(&rect)));
+        }
+
+        #[test]
+        fn prop_area_matches_coords(rect in small_rect()) {
+            prop_assert_eq!(rect.area(), Some(rect.iter_coords().count() as u64));
+        }
+
+        #[test]
+        fn prop_bounding_box_matches_coords(rects in prop::collection::vec(small_rect(), 0..5)) {
+            let all_coords: HashSet<_> = rects.iter().flat_map(coords).collect();
+
+            match Rect::bounding_box(&rects) {
+                None => prop_assert!(all_coords.is_empty()),
+                Some(bounds) => {
+                    let bounds_coords = coords(&bounds);
+
+                    prop_assert!(all_coords.is_subset(&bounds_coords));
+
+                    // every edge of the box touches some covered cell
+                    let (top_x, top_y) = bounds.top_right().unwrap();
+
+                    prop_assert!(all_coords.iter().any(|&(x, _)| x == bounds.x));
+                    prop_assert!(all_coords.iter().any(|&(x, _)| x == top_x));
+                    prop_assert!(all_coords.iter().any(|&(_, y)| y == bounds.y));
+                    prop_assert!(all_coords.iter().any(|&(_, y)| y == top_y));
+                },
+            }
+        }
+
+        #[test]
+        fn prop_subtract_matches_coords(rect in small_rect(), other in small_rect()) {
+            let expected: HashSet<_> = coords(&rect).difference(&coords(&other)).cloned().collect();
+            let pieces = rect.subtract(&other);
+
+            prop_assert!(pieces.len() <= 4);
+
+            let mut actual = HashSet::new();
+
+            for piece in &pieces {
+                prop_assert!(!piece.is_empty());
+
+                for coord in piece.iter_coords() {
+                    // pieces are disjoint
+                    prop_assert!(actual.insert(coord));
+                }
+            }
+
+            prop_assert_eq!(actual, expected);
+        }
+    }
+
     #[test]
     fn test_parsing() {
         let rect: Rect = "#1 @ 1,5: 9x100".parse().unwrap();
@@ -164,14 +935,126 @@ mod tests {
         let err = parse_result.unwrap_err();
 
         assert_eq!(
-            format!("{}", err), "Error parsing Rect from string: 'Elvish Pants'"
+            format!("{}", err),
+            "Error parsing Rect from string 'Elvish Pants': expected '#' at offset 0"
+        );
+    }
+
+    #[test]
+    fn test_parsing_is_anchored() {
+        let error_message = |s: &str| format!("{}", s.parse::<Rect>().unwrap_err());
+
+        assert_eq!(
+            error_message("garbage #1 @ 1,5: 9x100"),
+            "Error parsing Rect from string 'garbage #1 @ 1,5: 9x100': expected '#' at offset 0"
+        );
+
+        assert_eq!(
+            error_message("#1 @ 1,5: 9x100 trailing"),
+            "Error parsing Rect from string '#1 @ 1,5: 9x100 trailing': \
+             expected end of string at offset 16"
         );
     }
 
+    #[test]
+    fn test_parsing_names_field() {
+        let error_message = |s: &str| format!("{}", s.parse::<Rect>().unwrap_err());
+
+        assert_eq!(
+            error_message("#1 @ 1,: 9x100"),
+            "Error parsing Rect from string '#1 @ 1,: 9x100': expected y at offset 7"
+        );
+
+        assert_eq!(
+            error_message("#1 @ 1,5 9x100"),
+            "Error parsing Rect from string '#1 @ 1,5 9x100': expected ':' at offset 9"
+        );
+
+        // offsets count characters, not bytes
+        assert_eq!(
+            error_message("#1 @ 1,5: 9×100"),
+            "Error parsing Rect from string '#1 @ 1,5: 9×100': expected 'x' at offset 11"
+        );
+    }
+
+    #[test]
+    fn test_parsing_whitespace() {
+        let expected = Rect { id: 1, x: 1, y: 5, width: 9, height: 100 };
+
+        assert_eq!("  #1@1 , 5 :9 x 100\t".parse::<Rect>().unwrap(), expected);
+        assert_eq!("# 1 @ 1,5:  9x100".parse::<Rect>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parsing_strict() {
+        let expected = Rect { id: 1, x: 1, y: 5, width: 9, height: 100 };
+
+        assert_eq!(Rect::parse("#1 @ 1,5: 9x100", ParseMode::Strict).unwrap(), expected);
+
+        let err = Rect::<u32>::parse("#1 @ 1, 5: 9x100", ParseMode::Strict).unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 1, 5: 9x100': expected y at offset 7"
+        );
+
+        let err = Rect::<u32>::parse(" #1 @ 1,5: 9x100", ParseMode::Strict).unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string ' #1 @ 1,5: 9x100': expected '#' at offset 0"
+        );
+
+        let err = Rect::<u32>::parse("#1 @ 1,5: 9x100\n", ParseMode::Strict).unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 1,5: 9x100\n': expected end of string at offset 15"
+        );
+    }
+
+    #[test]
+    fn test_parsing_out_of_bounds() {
+        let max = u32::MAX;
+
+        // the last cell is still addressable
+        assert!(format!("#1 @ {},5: 1x1", max - 1).parse::<Rect>().is_ok());
+
+        let err = format!("#1 @ {},5: 1x1", max).parse::<Rect>().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 4294967295,5: 1x1': x + width exceeds the coordinate space"
+        );
+
+        let err = format!("#1 @ 5,1: 1x{}", max).parse::<Rect>().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 5,1: 1x4294967295': y + height exceeds the coordinate space"
+        );
+    }
+
+    #[test]
+    fn test_check_unique_ids() {
+        let mut rects = vec![
+            Rect { id: 1, x: 1, y: 3, width: 4, height: 4 },
+            Rect { id: 2, x: 3, y: 1, width: 4, height: 4 },
+            Rect { id: 3, x: 5, y: 5, width: 2, height: 2 },
+        ];
+
+        assert!(check_unique_ids(&rects).is_ok());
+
+        rects.push(Rect { id: 2, x: 0, y: 0, width: 1, height: 1 });
+
+        let err = check_unique_ids(&rects).unwrap_err();
+        assert_eq!(format!("{}", err), "Duplicate claim ID 2 on lines 2 and 4");
+    }
+
     #[test]
     fn test_parsing_int_overflow() {
-        assert!(999999999 < u32::max_value() as u64);
-        assert!(5000000000 > u32::max_value() as u64);
+        assert!(999999999 < u32::MAX as u64);
+        assert!(5000000000 > u32::MAX as u64);
 
         let parse_no_overflow = "#1 @ 1,5: 8x999999999".parse::<Rect>();
         assert!(parse_no_overflow.is_ok());
@@ -182,7 +1065,8 @@ mod tests {
         let err = parse_overflow.unwrap_err();
 
         assert_eq!(
-            format!("{}", err), "Error parsing Rect: integer overflow"
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 1,5: 8x5000000000': height overflows at offset 12"
         );
     }
 
@@ -195,4 +1079,69 @@ mod tests {
 
         assert_eq!(as_str.parse::<Rect>().unwrap(), rect);
     }
+
+    #[test]
+    fn test_signed_coordinates() {
+        let rect: Rect<i32> = "#1 @ -3,-2: 4x3".parse().unwrap();
+
+        assert_eq!(rect, Rect { id: 1, x: -3, y: -2, width: 4, height: 3 });
+        assert_eq!(rect.top_right().unwrap(), (0, 0));
+        assert!(rect.contains_point(-1, 0));
+        assert_eq!(format!("{}", rect), "#1 @ -3,-2: 4x3");
+
+        let err = "#1 @ 1,2: -4x3".parse::<Rect<i32>>().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ 1,2: -4x3': negative width"
+        );
+
+        // unsigned coordinates can't be negative at all
+        let err = "#1 @ -3,2: 4x3".parse::<Rect>().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#1 @ -3,2: 4x3': expected x at offset 5"
+        );
+    }
+
+    #[test]
+    fn test_wide_coordinates() {
+        let rect: Rect<u64> = "#1 @ 5000000000,1: 8x5000000000".parse().unwrap();
+
+        assert_eq!(rect.top_right().unwrap(), (5000000007, 5000000000));
+        assert_eq!(rect.area(), Some(40000000000));
+    }
+
+    #[test]
+    fn test_coordinate_type() {
+        assert_eq!("i64".parse::<CoordinateType>().unwrap(), CoordinateType::I64);
+
+        let err = "f32".parse::<CoordinateType>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown coordinate type: 'f32'");
+    }
+
+    #[test]
+    fn test_string_ids() {
+        let rect: Rect<u32, String> = "#kitchen @ 1,5: 9x100".parse().unwrap();
+
+        assert_eq!(rect.id, "kitchen");
+        assert_eq!(format!("{}", rect), "#kitchen @ 1,5: 9x100");
+
+        let rects: Vec<Rect<u32, String>> = ["#hall @ 0,0: 1x1", "#hall @ 2,2: 1x1"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+
+        let err = check_unique_ids(&rects).unwrap_err();
+        assert_eq!(format!("{}", err), "Duplicate claim ID hall on lines 1 and 2");
+
+        // numeric IDs reject anything else
+        let err = "#kitchen @ 1,5: 9x100".parse::<Rect>().unwrap_err();
+
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing Rect from string '#kitchen @ 1,5: 9x100': invalid id at offset 1"
+        );
+    }
 }
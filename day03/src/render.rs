@@ -0,0 +1,411 @@
+use std::cmp::max;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::overlaps::{Count, Overlaps};
+use crate::rect::{ClaimID, Coordinate};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    // binary portable pixmap, one pixel per cell
+    Ppm,
+    // downsampled true colour terminal heatmap
+    Ansi,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Unknown render format: '{}'", format)]
+pub struct UnknownRenderFormat {
+    format: String,
+}
+
+
+impl FromStr for RenderFormat {
+    type Err = UnknownRenderFormat;
+
+    fn from_str(s: &str) -> Result<RenderFormat, UnknownRenderFormat> {
+        match s {
+            "ppm" => Ok(RenderFormat::Ppm),
+            "ansi" => Ok(RenderFormat::Ansi),
+            _ => Err(UnknownRenderFormat { format: s.into() }),
+        }
+    }
+}
+
+
+type Colour = [u8; 3];
+
+const BACKGROUND: Colour = [0x20, 0x20, 0x20];
+const SINGLE_CLAIM: Colour = [0x40, 0x60, 0xa0];
+const ISOLATED_CLAIM: Colour = [0x30, 0xc0, 0x30];
+const SHALLOW_OVERLAP: Colour = [0xff, 0xe0, 0x40];
+const DEEP_OVERLAP: Colour = [0xff, 0x20, 0x20];
+const LABEL: Colour = [0xff, 0xff, 0xff];
+
+// 3x5 pixel digits, one row of 3 bits per byte, top row first
+const DIGIT_GLYPHS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+
+// largest render buffer to allocate
+const MAX_RENDER_BYTES: usize = 1 << 30;
+
+// bytes per pixel: its escape code and characters, with some room for
+// the cell and characters kept while rendering
+const ANSI_PIXEL_BYTES: usize = 64;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Render of {} pixels is too large", size)]
+pub struct RenderTooLarge {
+    pub size: String,
+}
+
+
+// ordered so that the max of several cells is the most interesting one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Cell {
+    depth: usize,
+    // part of a claim which doesn't overlap any other
+    isolated: bool,
+}
+
+
+// colours of the cells, the deepest overlaps being the reddest
+struct Palette {
+    max_depth: usize,
+}
+
+
+impl Palette {
+    fn new<T, C, I>(overlaps: &Overlaps<T, C, I>) -> Palette
+        where T: Count, C: Coordinate, I: ClaimID
+    {
+        let (width, height) = overlaps.grid_size();
+
+        let max_depth = (0..height)
+            .flat_map(|y| (0..width).map(move |x| overlaps.depth_at_cell(x, y)))
+            .max()
+            .unwrap_or(0);
+
+        Palette { max_depth }
+    }
+
+    fn colour(&self, cell: Cell) -> Colour {
+        match cell.depth {
+            0 => BACKGROUND,
+            1 if cell.isolated => ISOLATED_CLAIM,
+            1 => SINGLE_CLAIM,
+            depth => {
+                let span = max(self.max_depth.saturating_sub(2), 1);
+                blend(SHALLOW_OVERLAP, DEEP_OVERLAP, (depth - 2) as f64 / span as f64)
+            },
+        }
+    }
+}
+
+
+// `bytes_per_pixel` times `width` by `height`, if within MAX_RENDER_BYTES
+fn check_render_size(width: usize, height: usize, bytes_per_pixel: usize) -> Result<(), RenderTooLarge> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+        .filter(|&bytes| bytes <= MAX_RENDER_BYTES)
+        .map(|_| ())
+        .ok_or_else(|| RenderTooLarge { size: format!("{}x{}", width, height) })
+}
+
+
+// grid cells of the claims not overlapping any other
+fn isolated_cells<'a, T, C, I>(overlaps: &'a Overlaps<T, C, I>) -> impl Iterator<Item=(usize, usize)> + 'a
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    overlaps
+        .iter_non_overlapping_rects()
+        .flat_map(move |rect| rect.iter_coords().filter_map(move |(x, y)| overlaps.grid_position(x, y)))
+}
+
+
+fn blend(from: Colour, to: Colour, ratio: f64) -> Colour {
+    let mut colour = from;
+
+    for (channel, (&low, &high)) in colour.iter_mut().zip(from.iter().zip(to.iter())) {
+        *channel = (f64::from(low) + (f64::from(high) - f64::from(low)) * ratio).round() as u8;
+    }
+
+    colour
+}
+
+
+// on `pixels` of 3 bytes each
+fn draw_label(pixels: &mut [u8], width: usize, height: usize,
+              x: usize, y: usize, label: &str) {
+    let digits = label.chars().filter_map(|ch| ch.to_digit(10));
+
+    for (pos, digit) in digits.enumerate() {
+        // 3 pixels wide glyphs, 1 pixel apart
+        let glyph_x = x + pos * 4;
+
+        for (row, bits) in DIGIT_GLYPHS[digit as usize].iter().enumerate() {
+            for col in 0..3 {
+                let (pixel_x, pixel_y) = (glyph_x + col, y + row);
+
+                if bits & (0b100 >> col) != 0 && pixel_x < width && pixel_y < height {
+                    set_pixel(pixels, width, pixel_x, pixel_y, LABEL);
+                }
+            }
+        }
+    }
+}
+
+
+fn set_pixel(pixels: &mut [u8], width: usize, x: usize, y: usize, colour: Colour) {
+    let offset = (y * width + x) * 3;
+    pixels[offset..offset + 3].copy_from_slice(&colour);
+}
+
+
+/// Binary PPM image of the count grid, one pixel per cell, optionally
+/// with claim IDs drawn next to claims' corners (only their digits,
+/// the font has nothing else).
+pub fn render_ppm<T, C, I>(overlaps: &Overlaps<T, C, I>, labels: bool) -> Result<Vec<u8>, RenderTooLarge>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    let (width, height) = overlaps.grid_size();
+    check_render_size(width, height, 3)?;
+
+    let palette = Palette::new(overlaps);
+
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    let header_len = image.len();
+
+    image.reserve_exact(width * height * 3);
+
+    for y in 0..height {
+        for x in 0..width {
+            let cell = Cell { depth: overlaps.depth_at_cell(x, y), isolated: false };
+            image.extend_from_slice(&palette.colour(cell));
+        }
+    }
+
+    let pixels = &mut image[header_len..];
+
+    for (x, y) in isolated_cells(overlaps) {
+        set_pixel(pixels, width, x, y, ISOLATED_CLAIM);
+    }
+
+    if labels {
+        for rect in overlaps.rects().iter().filter(|rect| !rect.is_empty()) {
+            if let Some((x, y)) = overlaps.grid_position(rect.x, rect.y) {
+                draw_label(pixels, width, height, x + 1, y + 1, &rect.id.to_string());
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+
+/// Terminal heatmap using true colour escape codes, at most `columns`
+/// characters wide.  Each character pair shows the most interesting
+/// cell of a square block of cells.
+pub fn render_ansi<T, C, I>(overlaps: &Overlaps<T, C, I>, columns: usize, labels: bool)
+                            -> Result<String, RenderTooLarge>
+    where T: Count, C: Coordinate, I: ClaimID
+{
+    let (grid_width, grid_height) = overlaps.grid_size();
+
+    // two characters per block look roughly square in a terminal
+    let blocks_across = max(columns / 2, 1);
+    let scale = max(grid_width.div_ceil(blocks_across), 1);
+
+    let width = grid_width.div_ceil(scale);
+    let height = grid_height.div_ceil(scale);
+
+    check_render_size(width, height, ANSI_PIXEL_BYTES)?;
+
+    let palette = Palette::new(overlaps);
+    let mut blocks = vec![Cell::default(); width * height];
+
+    for y in 0..grid_height {
+        for x in 0..grid_width {
+            let block = &mut blocks[(y / scale) * width + x / scale];
+            *block = max(*block, Cell { depth: overlaps.depth_at_cell(x, y), isolated: false });
+        }
+    }
+
+    for (x, y) in isolated_cells(overlaps) {
+        let block = &mut blocks[(y / scale) * width + x / scale];
+        *block = max(*block, Cell { depth: 1, isolated: true });
+    }
+
+    let mut text = vec![' '; width * 2 * height];
+
+    if labels {
+        let positions = overlaps.rects()
+            .iter()
+            .filter(|rect| !rect.is_empty())
+            .filter_map(|rect| Some((rect, overlaps.grid_position(rect.x, rect.y)?)));
+
+        for (rect, (x, y)) in positions {
+            let row = y / scale;
+            let col = x / scale * 2;
+
+            // labels are cut off at the end of the line
+            for (pos, ch) in rect.id.to_string().chars().enumerate() {
+                if col + pos < width * 2 {
+                    text[row * width * 2 + col + pos] = ch;
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+
+    for row in 0..height {
+        for col in 0..width {
+            let [red, green, blue] = palette.colour(blocks[row * width + col]);
+            let chars = &text[(row * width + col) * 2..(row * width + col + 1) * 2];
+
+            // writing to a String can't fail
+            let _ = write!(out, "\x1b[97;48;2;{};{};{}m{}{}", red, green, blue, chars[0], chars[1]);
+        }
+
+        out.push_str("\x1b[0m\n");
+    }
+
+    Ok(out)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::overlaps::{Overlaps, RectOverlaps};
+    use crate::rect::Rect;
+
+    use super::{check_render_size, render_ansi, render_ppm, RenderFormat};
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("ppm".parse::<RenderFormat>().unwrap(), RenderFormat::Ppm);
+        assert_eq!("ansi".parse::<RenderFormat>().unwrap(), RenderFormat::Ansi);
+
+        let err = "png".parse::<RenderFormat>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown render format: 'png'");
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let rects = [
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 1 },
+            Rect { id: 2, x: 1, y: 0, width: 1, height: 2 },
+            Rect { id: 3, x: 3, y: 1, width: 1, height: 1 },
+        ];
+
+        let overlaps = RectOverlaps::new(&rects).unwrap();
+        let image = render_ppm(&overlaps, false).unwrap();
+
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&image[..header.len()], header);
+
+        let pixels: Vec<_> = image[header.len()..].chunks(3).collect();
+
+        assert_eq!(
+            pixels,
+            vec![
+                // single, overlap, empty, empty
+                &[0x40, 0x60, 0xa0], &[0xff, 0xe0, 0x40], &[0x20, 0x20, 0x20], &[0x20, 0x20, 0x20],
+                // empty, single, empty, isolated claim
+                &[0x20, 0x20, 0x20], &[0x40, 0x60, 0xa0], &[0x20, 0x20, 0x20], &[0x30, 0xc0, 0x30],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_ppm_labels() {
+        let rects = [Rect { id: 7, x: 0, y: 0, width: 5, height: 7 }];
+
+        let overlaps = RectOverlaps::new(&rects).unwrap();
+        let image = render_ppm(&overlaps, true).unwrap();
+
+        let header_len = b"P6\n5 7\n255\n".len();
+
+        let label_rows: Vec<String> = image[header_len..]
+            .chunks(3)
+            .map(|pixel| if pixel == [0xff, 0xff, 0xff] { '#' } else { '.' })
+            .collect::<Vec<_>>()
+            .chunks(5)
+            .map(|row| row.iter().collect())
+            .collect();
+
+        assert_eq!(
+            label_rows,
+            vec![".....", ".###.", "...#.", "..#..", "..#..", "..#..", "....."]
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let rects = [
+            Rect { id: 1, x: 0, y: 0, width: 2, height: 1 },
+            Rect { id: 23, x: 1, y: 0, width: 1, height: 1 },
+        ];
+
+        let overlaps = RectOverlaps::new(&rects).unwrap();
+
+        assert_eq!(
+            render_ansi(&overlaps, 4, false).unwrap(),
+            "\x1b[97;48;2;64;96;160m  \x1b[97;48;2;255;224;64m  \x1b[0m\n"
+        );
+
+        assert_eq!(
+            render_ansi(&overlaps, 4, true).unwrap(),
+            "\x1b[97;48;2;64;96;160m1 \x1b[97;48;2;255;224;64m23\x1b[0m\n"
+        );
+
+        // downsampled to a single block, showing the overlap
+        assert_eq!(
+            render_ansi(&overlaps, 2, false).unwrap(),
+            "\x1b[97;48;2;255;224;64m  \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let overlaps = RectOverlaps::new(&[]).unwrap();
+
+        assert_eq!(render_ppm(&overlaps, true).unwrap(), b"P6\n0 0\n255\n".to_vec());
+        assert_eq!(render_ansi(&overlaps, 80, true).unwrap(), "");
+    }
+
+    #[test]
+    fn test_render_too_large() {
+        assert!(check_render_size(1 << 14, 1 << 14, 3).is_ok());
+
+        let err = check_render_size(1 << 15, 1 << 14, 3).unwrap_err();
+        assert_eq!(format!("{}", err), "Render of 32768x16384 pixels is too large");
+
+        assert!(check_render_size(usize::MAX, 2, 1).is_err());
+
+        // downsampled to 40 blocks across, but not down
+        let rects = [Rect { id: 1, x: 0, y: 0, width: 1, height: (1 << 24) + 1 }];
+        let overlaps = Overlaps::<u8>::new(&rects).unwrap();
+
+        let err = render_ansi(&overlaps, 80, false).unwrap_err();
+        assert_eq!(format!("{}", err), "Render of 1x16777217 pixels is too large");
+    }
+}
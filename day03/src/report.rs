@@ -0,0 +1,220 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::rect::{ClaimID, RectIDType};
+
+
+/// How a single claim fares against all the others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClaimReport<I = RectIDType> {
+    pub id: I,
+    // number of the claim's cells covered by at least one other claim
+    pub contested_cells: usize,
+    // highest number of claims covering any of the claim's cells
+    pub max_depth: usize,
+    // IDs of other claims sharing cells with this one, sorted
+    pub conflicts: Vec<I>,
+}
+
+
+/// Answers to both parts of the puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Answers<I = RectIDType> {
+    pub overlap_area: usize,
+    pub non_overlapping_ids: Vec<I>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Unknown report format: '{}'", format)]
+pub struct UnknownReportFormat {
+    format: String,
+}
+
+
+impl FromStr for ReportFormat {
+    type Err = UnknownReportFormat;
+
+    fn from_str(s: &str) -> Result<ReportFormat, UnknownReportFormat> {
+        match s {
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(UnknownReportFormat { format: s.into() }),
+        }
+    }
+}
+
+
+pub fn format_reports<I: ClaimID>(reports: &[ClaimReport<I>], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Csv => format_csv(reports),
+        ReportFormat::Json => format_json(reports),
+    }
+}
+
+
+fn join_ids<I: ClaimID>(ids: &[I], format_id: fn(&I) -> String, separator: &str) -> String {
+    ids.iter()
+        .map(format_id)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+
+fn json_id<I: ClaimID>(id: &I) -> String {
+    if I::NUMERIC {
+        return id.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+
+    for ch in id.to_string().chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            // writing to a String can't fail
+            ch if ch.is_control() => { let _ = write!(quoted, "\\u{:04x}", ch as u32); },
+            ch => quoted.push(ch),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+
+fn format_csv<I: ClaimID>(reports: &[ClaimReport<I>]) -> String {
+    let mut out = String::from("id,contested_cells,max_depth,conflicts\n");
+
+    for report in reports {
+        // conflicts are space separated (IDs can't contain whitespace),
+        // string IDs may still need quoting
+        // (writing to a String can't fail)
+        let _ = writeln!(
+            out, "{},{},{},{}",
+            csv_field(&report.id.to_string()), report.contested_cells, report.max_depth,
+            csv_field(&join_ids(&report.conflicts, I::to_string, " "))
+        );
+    }
+
+    out
+}
+
+
+fn format_json<I: ClaimID>(reports: &[ClaimReport<I>]) -> String {
+    let rows: Vec<_> = reports
+        .iter()
+        .map(|report| {
+            format!(
+                "  {{\"id\": {}, \"contested_cells\": {}, \"max_depth\": {}, \"conflicts\": [{}]}}",
+                json_id(&report.id), report.contested_cells, report.max_depth,
+                join_ids(&report.conflicts, json_id, ", ")
+            )
+        })
+        .collect();
+
+    if rows.is_empty() {
+        "[]\n".into()
+    } else {
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaimReport, format_reports, ReportFormat};
+
+    fn example_reports() -> Vec<ClaimReport> {
+        vec![
+            ClaimReport { id: 1, contested_cells: 4, max_depth: 2, conflicts: vec![2] },
+            ClaimReport { id: 2, contested_cells: 4, max_depth: 2, conflicts: vec![1] },
+            ClaimReport { id: 3, contested_cells: 0, max_depth: 1, conflicts: vec![] },
+            ClaimReport { id: 4, contested_cells: 9, max_depth: 3, conflicts: vec![1, 2] },
+        ]
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("csv".parse::<ReportFormat>().unwrap(), ReportFormat::Csv);
+        assert_eq!("json".parse::<ReportFormat>().unwrap(), ReportFormat::Json);
+
+        let err = "xml".parse::<ReportFormat>().unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown report format: 'xml'");
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            format_reports(&example_reports(), ReportFormat::Csv),
+            "id,contested_cells,max_depth,conflicts\n\
+             1,4,2,2\n\
+             2,4,2,1\n\
+             3,0,1,\n\
+             4,9,3,1 2\n"
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_reports(&example_reports(), ReportFormat::Json),
+            "[\n  \
+             {\"id\": 1, \"contested_cells\": 4, \"max_depth\": 2, \"conflicts\": [2]},\n  \
+             {\"id\": 2, \"contested_cells\": 4, \"max_depth\": 2, \"conflicts\": [1]},\n  \
+             {\"id\": 3, \"contested_cells\": 0, \"max_depth\": 1, \"conflicts\": []},\n  \
+             {\"id\": 4, \"contested_cells\": 9, \"max_depth\": 3, \"conflicts\": [1, 2]}\n\
+             ]\n"
+        );
+
+        assert_eq!(format_reports::<u32>(&[], ReportFormat::Json), "[]\n");
+    }
+
+    #[test]
+    fn test_format_string_ids() {
+        let reports = vec![
+            ClaimReport {
+                id: String::from("hall"), contested_cells: 2, max_depth: 2,
+                conflicts: vec!["a,b".into(), "\"c\"".into()],
+            },
+            ClaimReport {
+                id: String::from("a,b"), contested_cells: 1, max_depth: 2, conflicts: vec!["hall".into()],
+            },
+        ];
+
+        assert_eq!(
+            format_reports(&reports, ReportFormat::Csv),
+            "id,contested_cells,max_depth,conflicts\n\
+             hall,2,2,\"a,b \"\"c\"\"\"\n\
+             \"a,b\",1,2,hall\n"
+        );
+
+        assert_eq!(
+            format_reports(&reports, ReportFormat::Json),
+            "[\n  \
+             {\"id\": \"hall\", \"contested_cells\": 2, \"max_depth\": 2, \"conflicts\": [\"a,b\", \"\\\"c\\\"\"]},\n  \
+             {\"id\": \"a,b\", \"contested_cells\": 1, \"max_depth\": 2, \"conflicts\": [\"hall\"]}\n\
+             ]\n"
+        );
+    }
+}
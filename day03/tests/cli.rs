@@ -4,7 +4,6 @@ mod cli {
     use std::process::Command;
 
     use assert_cmd::prelude::*;
-    use tempfile;
 
     #[test]
     fn test_run_input_file() {
@@ -16,7 +15,7 @@ mod cli {
         let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
         tmp_file.write_all(include_str!("../input/input").as_bytes()).unwrap();
 
-        cmd.arg(&tmp_file.path());
+        cmd.arg(tmp_file.path());
 
         cmd
             .assert()
@@ -24,4 +23,370 @@ mod cli {
             .stdout("Overlap area: 112378\n\
                      Non overlapping claim ID: 603\n");
     }
+
+    const EXAMPLE_INPUT: &str = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim ID: 3\n");
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--report", "csv"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("id,contested_cells,max_depth,conflicts\n\
+                     1,4,2,2\n\
+                     2,4,2,1\n\
+                     3,0,1,\n");
+    }
+
+    #[test]
+    fn test_report_json() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--report=json");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("[\n  \
+                     {\"id\": 1, \"contested_cells\": 4, \"max_depth\": 2, \"conflicts\": [2]},\n  \
+                     {\"id\": 2, \"contested_cells\": 4, \"max_depth\": 2, \"conflicts\": [1]},\n  \
+                     {\"id\": 3, \"contested_cells\": 0, \"max_depth\": 1, \"conflicts\": []}\n\
+                     ]\n");
+    }
+
+    #[test]
+    fn test_report_invalid_format() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        cmd
+            .args(["--report", "xml"])
+            .assert()
+            .failure()
+            .stderr("Error: Invalid value for option --report: 'xml' (Unknown report format: 'xml'). Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_option_values() {
+        let cases = [
+            ("--fill", "rows", "Unknown fill method: 'rows'"),
+            ("--cell-type", "i8", "Unknown cell type: 'i8'"),
+            ("--render", "gif", "Unknown render format: 'gif'"),
+            ("--input-format", "yaml", "Unknown input format: 'yaml'"),
+            ("--coordinates", "f32", "Unknown coordinate type: 'f32'"),
+            ("--width", "wide", "invalid digit found in string"),
+        ];
+
+        for &(option, value, reason) in &cases {
+            let mut cmd = Command::main_binary().unwrap();
+
+            cmd
+                .args([option, value])
+                .assert()
+                .failure()
+                .stderr(format!("Error: Invalid value for option {}: '{}' ({}). Aborting.\n", option, value, reason));
+        }
+    }
+
+    #[test]
+    fn test_threshold_and_histogram() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--threshold", "1", "--histogram"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim ID: 3\n\
+                     Area covered by at least 1 claims: 32\n\
+                     Overlap depth histogram:\n\
+                     1: 28\n\
+                     2: 4\n");
+    }
+
+    #[test]
+    fn test_cell_type_too_small() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(include_str!("../input/input").as_bytes()).unwrap();
+
+        cmd.args(["--cell-type", "u8"]).arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .failure()
+            .stderr(predicates::str::ends_with(
+                "Error: Cell type u8 can't count up to 1331 claims. Aborting.\n"
+            ));
+    }
+
+    #[test]
+    fn test_render_ppm_to_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let output_file = tempfile::NamedTempFile::new().unwrap();
+
+        cmd.args(["--render", "ppm", "--output"]).arg(output_file.path());
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("");
+
+        let image = std::fs::read(output_file.path()).unwrap();
+        // the grid starts at the minimum corner of the claims, (1, 1)
+        let header = b"P6\n6 6\n255\n";
+
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 6 * 6 * 3);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--render", "ansi", "--width", "8", "--labels"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        // 6x6 grid from (1, 1) in 3x3 cell blocks, each drawn with escape
+        // codes for the background colour (empty, single, overlap, isolated)
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("\x1b[97;48;2;32;32;32m  \x1b[97;48;2;64;96;160m2 \
+                     \x1b[97;48;2;64;96;160m  \x1b[0m\n\
+                     \x1b[97;48;2;64;96;160m1 \x1b[97;48;2;255;224;64m  \
+                     \x1b[97;48;2;64;96;160m  \x1b[0m\n\
+                     \x1b[97;48;2;64;96;160m  \x1b[97;48;2;64;96;160m  \
+                     \x1b[97;48;2;48;192;48m3 \x1b[0m\n");
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--strict");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("#1 @ 1,3: 4x4\n#2 @ 3,1 : 4x4");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Rect from string '#2 @ 3,1 : 4x4': \
+                     expected ': ' at offset 8. Aborting.\n");
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#1 @ 5,5: 2x2");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Duplicate claim ID 1 on lines 1 and 3. Aborting.\n");
+    }
+
+    #[test]
+    fn test_signed_coordinates_and_string_ids() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--coordinates", "i64", "--string-ids"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("#kitchen @ -5,-5: 4x4\n#hall @ -3,-3: 4x4\n#study @ 2,2: 1x1");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim ID: study\n");
+    }
+
+    #[test]
+    fn test_fabric() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--fabric", "8x8"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(format!("{}\n#4 @ 7,0: 2x2\n#5 @ 9,9: 1x1", EXAMPLE_INPUT));
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim IDs: [3, 4, 5]\n\
+                     Claim 4 is partially outside the fabric (2 cells)\n\
+                     Claim 5 is outside the fabric\n\
+                     Unclaimed area: 30\n\
+                     Fabric utilisation: 53.12%\n");
+    }
+
+    #[test]
+    fn test_fill_differences() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--fill", "differences", "--fabric", "8x8", "--histogram"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim ID: 3\n\
+                     Unclaimed area: 32\n\
+                     Fabric utilisation: 50.00%\n\
+                     Overlap depth histogram:\n\
+                     1: 28\n\
+                     2: 4\n");
+    }
+
+    #[test]
+    fn test_invalid_fabric() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        // fails before reading any input
+        cmd
+            .args(["--fabric", "8by8"])
+            .assert()
+            .failure()
+            .stderr("Error: Invalid fabric size: '8by8', expected WIDTHxHEIGHT. Aborting.\n");
+    }
+
+    #[test]
+    fn test_optimise() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--optimise", "--fabric", "5x2"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("#1 @ 0,0: 2x2\n#2 @ 0,0: 2x2");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("#1 @ 2,0: 2x2\n\
+                     #2 @ 0,0: 2x2\n")
+            .stderr("Reading input from stdin.\n\
+                     Moved claim 1 from 0,0 to 2,0\n\
+                     Overlap area: 4 -> 0\n");
+    }
+
+    #[test]
+    fn test_optimise_needs_fabric() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--optimise");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Missing value for option: --fabric. Aborting.\n");
+    }
+
+    // `main_binary` rebuilds the binary without the crate's features,
+    // this is the one cargo built for the tests
+    #[cfg(feature = "serde")]
+    fn serde_binary() -> Command {
+        Command::new(env!("CARGO_BIN_EXE_day03"))
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_input_file() {
+        let mut cmd = serde_binary();
+
+        let mut tmp_file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        tmp_file.write_all(br#"[
+            {"id": 1, "x": 1, "y": 3, "width": 4, "height": 4},
+            {"id": 2, "x": 3, "y": 1, "width": 4, "height": 4},
+            {"id": 3, "x": 5, "y": 5, "width": 2, "height": 2}
+        ]"#).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Overlap area: 4\n\
+                     Non overlapping claim ID: 3\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_csv_input_and_json_answers() {
+        let mut cmd = serde_binary();
+        cmd.args(["--input-format", "csv", "--json", "--string-ids"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("id,x,y,width,height\nhall,1,3,4,4\nkitchen,3,1,4,4\nstudy,5,5,2,2\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("{\"overlap_area\":4,\"non_overlapping_ids\":[\"study\"]}\n");
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn test_json_needs_serde() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--input-format", "json"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("[]");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Reading JSON claims requires the serde feature. Aborting.\n");
+
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--json");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: JSON output requires the serde feature. Aborting.\n");
+    }
 }
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use common::Fail;
+
+
+pub type GuardID = u32;
+
+
+/// Minute precision timestamp.  Fields are in order of significance,
+/// so the derived ordering is chronological.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    BeginShift(GuardID),
+    FallAsleep,
+    WakeUp,
+}
+
+
+/// Line of the guard log, e.g. "[1518-11-01 00:05] falls asleep".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Event {
+    pub timestamp: Timestamp,
+    pub action: Action,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ParseEventError {
+    #[fail(display = "Error parsing Event from string: '{}'", string)]
+    MalformedString {
+        string: String,
+    },
+
+    #[fail(display = "Error parsing Event from string '{}': invalid timestamp", string)]
+    InvalidTimestamp {
+        string: String,
+    },
+
+    #[fail(display = "Error parsing Event from string '{}': unknown action '{}'", string, action)]
+    UnknownAction {
+        string: String,
+        action: String,
+    },
+}
+
+
+impl Timestamp {
+    pub fn same_day(&self, other: &Timestamp) -> bool {
+        (self.year, self.month, self.day) == (other.year, other.month, other.day)
+    }
+
+    // not checking days per month, the log only needs to be ordered
+    fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month) && (1..=31).contains(&self.day) && self.hour < 24 && self.minute < 60
+    }
+}
+
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute)
+    }
+}
+
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::BeginShift(guard) => write!(f, "Guard #{} begins shift", guard),
+            Action::FallAsleep => write!(f, "falls asleep"),
+            Action::WakeUp => write!(f, "wakes up"),
+        }
+    }
+}
+
+
+impl FromStr for Event {
+    type Err = ParseEventError;
+
+    fn from_str(s: &str) -> Result<Event, ParseEventError> {
+        lazy_static! {
+            static ref PATTERN: Regex = Regex::new(
+                r"^\[(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2}) (?P<hour>\d{2}):(?P<minute>\d{2})\] (?P<action>.+)$"
+            ).expect("Invalid regex pattern");
+
+            static ref SHIFT_PATTERN: Regex = Regex::new(
+                r"^Guard #(?P<guard>\d+) begins shift$"
+            ).expect("Invalid regex pattern");
+        }
+
+        let caps = PATTERN
+            .captures(s.trim())
+            .ok_or_else(|| ParseEventError::MalformedString { string: s.into() })?;
+
+        let invalid_timestamp = || ParseEventError::InvalidTimestamp { string: s.into() };
+
+        // a fixed number of digits always fits
+        let timestamp = Timestamp {
+            year: caps["year"].parse().map_err(|_| invalid_timestamp())?,
+            month: caps["month"].parse().map_err(|_| invalid_timestamp())?,
+            day: caps["day"].parse().map_err(|_| invalid_timestamp())?,
+            hour: caps["hour"].parse().map_err(|_| invalid_timestamp())?,
+            minute: caps["minute"].parse().map_err(|_| invalid_timestamp())?,
+        };
+
+        if !timestamp.is_valid() {
+            return Err(invalid_timestamp());
+        }
+
+        let unknown_action = || ParseEventError::UnknownAction { string: s.into(), action: caps["action"].into() };
+
+        let action = match &caps["action"] {
+            "falls asleep" => Action::FallAsleep,
+            "wakes up" => Action::WakeUp,
+            action => {
+                let shift = SHIFT_PATTERN.captures(action).ok_or_else(unknown_action)?;
+
+                // too many digits for a guard ID
+                Action::BeginShift(shift["guard"].parse().map_err(|_| unknown_action())?)
+            },
+        };
+
+        Ok(Event { timestamp, action })
+    }
+}
+
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}", self.timestamp, self.action)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Event, Timestamp};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "[1518-11-01 00:00] Guard #10 begins shift".parse::<Event>().unwrap(),
+            Event {
+                timestamp: Timestamp { year: 1518, month: 11, day: 1, hour: 0, minute: 0 },
+                action: Action::BeginShift(10),
+            }
+        );
+
+        assert_eq!(
+            "[1518-11-01 00:05] falls asleep".parse::<Event>().unwrap().action,
+            Action::FallAsleep
+        );
+
+        assert_eq!(
+            "[1518-11-01 23:58] wakes up".parse::<Event>().unwrap().timestamp,
+            Timestamp { year: 1518, month: 11, day: 1, hour: 23, minute: 58 }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "1518-11-01 00:05 falls asleep".parse::<Event>().unwrap_err();
+        assert_eq!(format!("{}", err), "Error parsing Event from string: '1518-11-01 00:05 falls asleep'");
+
+        let err = "[1518-13-01 00:05] falls asleep".parse::<Event>().unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Error parsing Event from string '[1518-13-01 00:05] falls asleep': invalid timestamp");
+
+        assert!("[1518-11-01 24:00] falls asleep".parse::<Event>().is_err());
+        assert!("[1518-11-00 00:00] falls asleep".parse::<Event>().is_err());
+
+        let err = "[1518-11-01 00:05] dozes off".parse::<Event>().unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Error parsing Event from string '[1518-11-01 00:05] dozes off': unknown action 'dozes off'");
+
+        assert!("[1518-11-01 00:00] Guard #99999999999 begins shift".parse::<Event>().is_err());
+        assert!("[1518-11-01 00:00] Guard #-1 begins shift".parse::<Event>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        for line in &["[1518-11-01 00:00] Guard #10 begins shift",
+                      "[1518-03-09 00:05] falls asleep",
+                      "[0018-11-01 23:59] wakes up"] {
+            assert_eq!(line.parse::<Event>().unwrap().to_string(), *line);
+        }
+    }
+
+    #[test]
+    fn test_chronological_order() {
+        let mut events: Vec<Event> = [
+            "[1518-11-02 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:30] wakes up",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+        ].iter().map(|line| line.parse().unwrap()).collect();
+
+        events.sort_by_key(|event| event.timestamp);
+
+        let minutes: Vec<_> = events.iter().map(|event| (event.timestamp.day, event.timestamp.minute)).collect();
+        assert_eq!(minutes, vec![(1, 5), (1, 30), (1, 58), (2, 0)]);
+    }
+}
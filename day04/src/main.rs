@@ -0,0 +1,57 @@
+use std::env;
+
+use common::{get_input, Error};
+
+mod event;
+mod shifts;
+
+use crate::event::Event;
+use crate::shifts::{reconstruct_shifts, Choice, SleepLog};
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    match run(&args) {
+        Err(err) => {
+            eprintln!("Error: {}. Aborting.", err);
+            std::process::exit(1);
+        },
+        Ok(false) => {
+            println!("No guard ever falls asleep!");
+            std::process::exit(2);
+        },
+        Ok(true) => {},
+    }
+
+    Ok(())
+}
+
+
+// whether any guard slept, giving answers
+fn run(args: &[String]) -> Result<bool, Error> {
+    let events: Vec<Event> = get_input(args)?;
+
+    let shifts = reconstruct_shifts(&events)?;
+    let log = SleepLog::new(&shifts);
+
+    let (sleepiest_guard, most_frequent_minute) = match (log.sleepiest_guard(), log.most_frequent_minute()) {
+        (Some(sleepiest_guard), Some(most_frequent_minute)) => (sleepiest_guard, most_frequent_minute),
+        _ => return Ok(false),
+    };
+
+    // part 1
+
+    print_choice(1, &sleepiest_guard);
+
+    // part 2
+
+    print_choice(2, &most_frequent_minute);
+
+    Ok(true)
+}
+
+
+fn print_choice(strategy: usize, choice: &Choice) {
+    println!("Strategy {}: guard #{}, minute {}: {}", strategy, choice.guard, choice.minute, choice.answer());
+}
@@ -0,0 +1,314 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use common::Fail;
+
+use crate::event::{Action, Event, GuardID, Timestamp};
+
+
+// guards only ever sleep during the midnight hour
+pub const MINUTES: usize = 60;
+
+
+/// One guard's shift, with the minutes of the midnight hour spent
+/// asleep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+    pub guard: GuardID,
+    pub start: Timestamp,
+    pub naps: Vec<Range<u8>>,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ShiftError {
+    #[fail(display = "No guard on duty: {}", event)]
+    NoGuard {
+        event: Event,
+    },
+
+    #[fail(display = "Unexpected event for guard #{}: {}", guard, event)]
+    UnexpectedEvent {
+        guard: GuardID,
+        event: Event,
+    },
+
+    #[fail(display = "Guard #{} naps outside the midnight hour: {}", guard, event)]
+    NapOutsideMidnight {
+        guard: GuardID,
+        event: Event,
+    },
+
+    #[fail(display = "Guard #{} never wakes up", guard)]
+    NeverWakesUp {
+        guard: GuardID,
+    },
+}
+
+
+/// Guard's most slept minute, chosen by one of the strategies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Choice {
+    pub guard: GuardID,
+    pub minute: u8,
+    // number of shifts the guard was asleep at that minute
+    pub times_asleep: usize,
+}
+
+
+/// Minutes of the midnight hour each guard was asleep, counted over
+/// all their shifts.
+#[derive(Debug, Default)]
+pub struct SleepLog {
+    minutes: BTreeMap<GuardID, [usize; MINUTES]>,
+}
+
+
+struct OnDuty {
+    shift: Shift,
+    // when the guard fell asleep, while asleep
+    asleep_since: Option<Timestamp>,
+}
+
+
+/// Shifts in chronological order, from events in any order.  Events
+/// with the same timestamp keep their relative order.
+pub fn reconstruct_shifts(events: &[Event]) -> Result<Vec<Shift>, ShiftError> {
+    let mut events = events.to_vec();
+    events.sort_by_key(|event| event.timestamp);
+
+    let mut shifts = vec![];
+    let mut on_duty: Option<OnDuty> = None;
+
+    for event in events {
+        if let Action::BeginShift(guard) = event.action {
+            if let Some(previous) = on_duty.take() {
+                shifts.push(finish_shift(previous)?);
+            }
+
+            let shift = Shift { guard, start: event.timestamp, naps: vec![] };
+            on_duty = Some(OnDuty { shift, asleep_since: None });
+
+            continue;
+        }
+
+        let duty = on_duty.as_mut().ok_or(ShiftError::NoGuard { event })?;
+        let guard = duty.shift.guard;
+
+        if event.timestamp.hour != 0 {
+            return Err(ShiftError::NapOutsideMidnight { guard, event });
+        }
+
+        match (event.action, duty.asleep_since) {
+            (Action::FallAsleep, None) => duty.asleep_since = Some(event.timestamp),
+            (Action::WakeUp, Some(since))
+                if since.same_day(&event.timestamp) && since.minute < event.timestamp.minute => {
+                duty.shift.naps.push(since.minute..event.timestamp.minute);
+                duty.asleep_since = None;
+            },
+            _ => return Err(ShiftError::UnexpectedEvent { guard, event }),
+        }
+    }
+
+    if let Some(last) = on_duty {
+        shifts.push(finish_shift(last)?);
+    }
+
+    Ok(shifts)
+}
+
+
+fn finish_shift(duty: OnDuty) -> Result<Shift, ShiftError> {
+    match duty.asleep_since {
+        Some(_) => Err(ShiftError::NeverWakesUp { guard: duty.shift.guard }),
+        None => Ok(duty.shift),
+    }
+}
+
+
+impl SleepLog {
+    pub fn new(shifts: &[Shift]) -> SleepLog {
+        let mut log = SleepLog::default();
+
+        for shift in shifts {
+            let minutes = log.minutes.entry(shift.guard).or_insert([0; MINUTES]);
+
+            for nap in &shift.naps {
+                for minute in nap.clone() {
+                    minutes[usize::from(minute)] += 1;
+                }
+            }
+        }
+
+        log
+    }
+
+    pub fn minutes_asleep(&self, guard: GuardID) -> usize {
+        self.minutes.get(&guard).map_or(0, |minutes| minutes.iter().sum())
+    }
+
+    /// Minute the guard was most often asleep at, the earliest one on
+    /// ties.  `None` if the guard never slept.
+    pub fn sleepiest_minute(&self, guard: GuardID) -> Option<Choice> {
+        let minutes = self.minutes.get(&guard)?;
+
+        minutes
+            .iter()
+            .enumerate()
+            .filter(|(_, &times_asleep)| times_asleep > 0)
+            .max_by_key(|&(minute, &times_asleep)| (times_asleep, Reverse(minute)))
+            .map(|(minute, &times_asleep)| Choice { guard, minute: minute as u8, times_asleep })
+    }
+
+    /// Strategy 1: the sleepiest minute of the guard asleep the most
+    /// minutes overall (the lowest ID on ties).
+    pub fn sleepiest_guard(&self) -> Option<Choice> {
+        let guard = self.minutes
+            .keys()
+            .cloned()
+            .filter(|&guard| self.minutes_asleep(guard) > 0)
+            .max_by_key(|&guard| (self.minutes_asleep(guard), Reverse(guard)))?;
+
+        self.sleepiest_minute(guard)
+    }
+
+    /// Strategy 2: the guard and minute most often slept at, of all
+    /// guards (the lowest ID on ties).
+    pub fn most_frequent_minute(&self) -> Option<Choice> {
+        self.minutes
+            .keys()
+            .filter_map(|&guard| self.sleepiest_minute(guard))
+            .max_by_key(|choice| (choice.times_asleep, Reverse(choice.guard)))
+    }
+}
+
+
+impl Choice {
+    /// Guard ID multiplied by the minute, the puzzle answer.
+    pub fn answer(&self) -> u64 {
+        u64::from(self.guard) * u64::from(self.minute)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::event::Event;
+
+    use super::{reconstruct_shifts, Choice, SleepLog};
+
+    const EXAMPLE_LOG: &[&str] = &[
+        "[1518-11-01 00:00] Guard #10 begins shift",
+        "[1518-11-01 00:05] falls asleep",
+        "[1518-11-01 00:25] wakes up",
+        "[1518-11-01 00:30] falls asleep",
+        "[1518-11-01 00:55] wakes up",
+        "[1518-11-01 23:58] Guard #99 begins shift",
+        "[1518-11-02 00:40] falls asleep",
+        "[1518-11-02 00:50] wakes up",
+        "[1518-11-03 00:05] Guard #10 begins shift",
+        "[1518-11-03 00:24] falls asleep",
+        "[1518-11-03 00:29] wakes up",
+        "[1518-11-04 00:02] Guard #99 begins shift",
+        "[1518-11-04 00:36] falls asleep",
+        "[1518-11-04 00:46] wakes up",
+        "[1518-11-05 00:03] Guard #99 begins shift",
+        "[1518-11-05 00:45] falls asleep",
+        "[1518-11-05 00:55] wakes up",
+    ];
+
+    fn events(lines: &[&str]) -> Vec<Event> {
+        lines.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_reconstruct_shifts() {
+        // the log arrives unsorted
+        let mut lines = EXAMPLE_LOG.to_vec();
+        lines.reverse();
+
+        let shifts = reconstruct_shifts(&events(&lines)).unwrap();
+
+        let guards: Vec<_> = shifts.iter().map(|shift| shift.guard).collect();
+        assert_eq!(guards, vec![10, 99, 10, 99, 99]);
+
+        assert_eq!(shifts[0].naps, vec![5..25, 30..55]);
+        assert_eq!(shifts[1].naps, vec![40..50]);
+    }
+
+    #[test]
+    fn test_strategies() {
+        let log = SleepLog::new(&reconstruct_shifts(&events(EXAMPLE_LOG)).unwrap());
+
+        assert_eq!(log.minutes_asleep(10), 50);
+        assert_eq!(log.minutes_asleep(99), 30);
+        assert_eq!(log.minutes_asleep(1), 0);
+
+        let choice = log.sleepiest_guard().unwrap();
+        assert_eq!(choice, Choice { guard: 10, minute: 24, times_asleep: 2 });
+        assert_eq!(choice.answer(), 240);
+
+        let choice = log.most_frequent_minute().unwrap();
+        assert_eq!(choice, Choice { guard: 99, minute: 45, times_asleep: 3 });
+        assert_eq!(choice.answer(), 4455);
+    }
+
+    #[test]
+    fn test_nobody_sleeps() {
+        let log = SleepLog::new(&reconstruct_shifts(&events(&["[1518-11-01 00:00] Guard #10 begins shift"])).unwrap());
+
+        assert_eq!(log.sleepiest_guard(), None);
+        assert_eq!(log.most_frequent_minute(), None);
+        assert_eq!(SleepLog::new(&[]).sleepiest_guard(), None);
+    }
+
+    #[test]
+    fn test_ties() {
+        let log = SleepLog::new(&reconstruct_shifts(&events(&[
+            "[1518-11-01 00:00] Guard #20 begins shift",
+            "[1518-11-01 00:10] falls asleep",
+            "[1518-11-01 00:12] wakes up",
+            "[1518-11-02 00:00] Guard #7 begins shift",
+            "[1518-11-02 00:30] falls asleep",
+            "[1518-11-02 00:32] wakes up",
+        ])).unwrap());
+
+        // the lowest guard ID, then the earliest minute
+        assert_eq!(log.sleepiest_guard(), Some(Choice { guard: 7, minute: 30, times_asleep: 1 }));
+        assert_eq!(log.most_frequent_minute(), Some(Choice { guard: 7, minute: 30, times_asleep: 1 }));
+    }
+
+    #[test]
+    fn test_invalid_logs() {
+        let error = |lines: &[&str]| reconstruct_shifts(&events(lines)).unwrap_err().to_string();
+
+        assert_eq!(error(&["[1518-11-01 00:05] falls asleep"]),
+                   "No guard on duty: [1518-11-01 00:05] falls asleep");
+
+        assert_eq!(error(&["[1518-11-01 00:00] Guard #10 begins shift", "[1518-11-01 00:05] wakes up"]),
+                   "Unexpected event for guard #10: [1518-11-01 00:05] wakes up");
+
+        assert_eq!(error(&["[1518-11-01 00:00] Guard #10 begins shift",
+                           "[1518-11-01 00:05] falls asleep",
+                           "[1518-11-01 00:07] falls asleep"]),
+                   "Unexpected event for guard #10: [1518-11-01 00:07] falls asleep");
+
+        assert_eq!(error(&["[1518-11-01 23:00] Guard #10 begins shift", "[1518-11-01 23:05] falls asleep"]),
+                   "Guard #10 naps outside the midnight hour: [1518-11-01 23:05] falls asleep");
+
+        assert_eq!(error(&["[1518-11-01 00:00] Guard #10 begins shift",
+                           "[1518-11-01 00:05] falls asleep",
+                           "[1518-11-02 00:00] Guard #11 begins shift"]),
+                   "Guard #10 never wakes up");
+
+        assert_eq!(error(&["[1518-11-01 00:00] Guard #10 begins shift", "[1518-11-01 00:05] falls asleep"]),
+                   "Guard #10 never wakes up");
+
+        // waking up the next night isn't a nap
+        assert_eq!(error(&["[1518-11-01 00:00] Guard #10 begins shift",
+                           "[1518-11-01 00:05] falls asleep",
+                           "[1518-11-02 00:01] wakes up"]),
+                   "Unexpected event for guard #10: [1518-11-02 00:01] wakes up");
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the example from Advent of Code, shuffled
+    const EXAMPLE_INPUT: &str = "\
+        [1518-11-01 00:05] falls asleep\n\
+        [1518-11-03 00:24] falls asleep\n\
+        [1518-11-01 00:00] Guard #10 begins shift\n\
+        [1518-11-01 00:25] wakes up\n\
+        [1518-11-01 00:30] falls asleep\n\
+        [1518-11-05 00:55] wakes up\n\
+        [1518-11-01 00:55] wakes up\n\
+        [1518-11-01 23:58] Guard #99 begins shift\n\
+        [1518-11-02 00:40] falls asleep\n\
+        [1518-11-02 00:50] wakes up\n\
+        [1518-11-03 00:05] Guard #10 begins shift\n\
+        [1518-11-03 00:29] wakes up\n\
+        [1518-11-04 00:02] Guard #99 begins shift\n\
+        [1518-11-04 00:36] falls asleep\n\
+        [1518-11-04 00:46] wakes up\n\
+        [1518-11-05 00:03] Guard #99 begins shift\n\
+        [1518-11-05 00:45] falls asleep\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Strategy 1: guard #10, minute 24: 240\n\
+                     Strategy 2: guard #99, minute 45: 4455\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Strategy 1: guard #10, minute 24: 240\n\
+                     Strategy 2: guard #99, minute 45: 4455\n");
+    }
+
+    #[test]
+    fn test_nobody_sleeps() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("[1518-11-01 00:00] Guard #10 begins shift\n");
+
+        assert_cmd
+            .assert()
+            .code(2)
+            .stdout("No guard ever falls asleep!\n");
+    }
+
+    #[test]
+    fn test_malformed_event() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("[1518-11-01 00:00] Guard #10 begins shift\n\
+                                               [1518-11-01 00:05] dozes off\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Event from string '[1518-11-01 00:05] dozes off': \
+                     unknown action 'dozes off'. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_log() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("[1518-11-01 00:05] falls asleep\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: No guard on duty: [1518-11-01 00:05] falls asleep. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_num_of_args() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        cmd
+            .arg("blah")
+            .arg("blah");
+
+        cmd
+            .assert()
+            .failure()
+            .stderr("Error: Invalid number of arguments: 2. Aborting.\n");
+    }
+}
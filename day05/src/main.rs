@@ -0,0 +1,41 @@
+use std::env;
+
+use common::{get_input_text, Error};
+
+mod polymer;
+
+use crate::polymer::{best_improvement, opposite_polarity, parse_polymer, reduce};
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    // a single line polymer, too long to bother splitting into lines
+    let input = get_input_text(&args).and_then(|text| Ok(parse_polymer(&text)?));
+
+    if let Err(err) = input {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    let units = input.unwrap();
+
+    // part 1
+
+    println!("Reduced polymer length: {}", reduce(units.iter().cloned(), opposite_polarity).len());
+
+    // part 2
+
+    match best_improvement(&units, opposite_polarity) {
+        Some(improvement) => {
+            println!("Shortest polymer length: {} (without {}/{})",
+                     improvement.length, improvement.unit_type, improvement.unit_type.to_ascii_uppercase());
+        },
+        None => {
+            println!("No unit types to remove!");
+            std::process::exit(2);
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+
+use common::Fail;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Invalid unit '{}' at position {}", unit, position)]
+pub struct InvalidUnit {
+    pub unit: char,
+    pub position: usize,
+}
+
+
+/// Shortest polymer found by removing all units of one type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Improvement {
+    // lowercase letter of the removed type
+    pub unit_type: char,
+    pub length: usize,
+}
+
+
+/// Polymer units from the input, ignoring the trailing line break.
+/// Units are ASCII letters, the case being their polarity.
+pub fn parse_polymer(text: &str) -> Result<Vec<char>, InvalidUnit> {
+    text.trim_end()
+        .chars()
+        .enumerate()
+        .map(|(position, unit)| {
+            if unit.is_ascii_alphabetic() {
+                Ok(unit)
+            } else {
+                Err(InvalidUnit { unit, position })
+            }
+        })
+        .collect()
+}
+
+
+/// Same type, opposite polarity units react, e.g. "aA" or "Bb".
+pub fn opposite_polarity(left: char, right: char) -> bool {
+    left != right && left.eq_ignore_ascii_case(&right)
+}
+
+
+/// Polymer left after all reactions, in a single pass: each unit
+/// either reacts with the last unit left so far, removing both, or
+/// is kept.  `reacts` is given the earlier unit first.
+pub fn reduce<T, F>(units: impl IntoIterator<Item=T>, reacts: F) -> Vec<T>
+    where T: Copy,
+          F: Fn(T, T) -> bool
+{
+    let mut stack: Vec<T> = vec![];
+
+    for unit in units {
+        match stack.last() {
+            Some(&last) if reacts(last, unit) => {
+                stack.pop();
+            },
+            _ => stack.push(unit),
+        }
+    }
+
+    stack
+}
+
+
+/// Removes each unit type (both polarities) in turn, returning the
+/// type giving the shortest reduced polymer, the earliest letter on
+/// ties.  `None` for an empty polymer.
+pub fn best_improvement<F>(units: &[char], reacts: F) -> Option<Improvement>
+    where F: Fn(char, char) -> bool
+{
+    let unit_types: BTreeSet<char> = units.iter().map(|unit| unit.to_ascii_lowercase()).collect();
+
+    unit_types
+        .into_iter()
+        .map(|unit_type| {
+            let kept = units.iter().cloned().filter(|unit| !unit.eq_ignore_ascii_case(&unit_type));
+
+            Improvement { unit_type, length: reduce(kept, &reacts).len() }
+        })
+        .min_by_key(|improvement| improvement.length)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{best_improvement, opposite_polarity, parse_polymer, reduce, Improvement};
+
+    const EXAMPLE: &str = "dabAcCaCBAcCcaDA";
+
+    fn reduce_str(polymer: &str) -> String {
+        reduce(polymer.chars(), opposite_polarity).into_iter().collect()
+    }
+
+    #[test]
+    fn test_reduce() {
+        assert_eq!(reduce_str("aA"), "");
+        assert_eq!(reduce_str("abBA"), "");
+        assert_eq!(reduce_str("abAB"), "abAB");
+        assert_eq!(reduce_str("aabAAB"), "aabAAB");
+        assert_eq!(reduce_str(EXAMPLE), "dabCBAcaDA");
+        assert_eq!(reduce_str(""), "");
+    }
+
+    #[test]
+    fn test_other_rules() {
+        // digits adding up to 10 react
+        let reduced = reduce(vec![3, 1, 9, 7, 5, 5, 2], |left, right| left + right == 10);
+        assert_eq!(reduced, vec![2]);
+
+        // reactions only depend on the units' order
+        let reduced = reduce("abBA".chars(), |left, right| left == 'b' && right == 'B');
+        assert_eq!(reduced, vec!['a', 'A']);
+    }
+
+    #[test]
+    fn test_best_improvement() {
+        let units: Vec<char> = EXAMPLE.chars().collect();
+
+        assert_eq!(
+            best_improvement(&units, opposite_polarity),
+            Some(Improvement { unit_type: 'c', length: 4 })
+        );
+
+        // ties go to the earliest letter
+        let units: Vec<char> = "aB".chars().collect();
+        assert_eq!(best_improvement(&units, opposite_polarity), Some(Improvement { unit_type: 'a', length: 1 }));
+
+        assert_eq!(best_improvement(&[], opposite_polarity), None);
+    }
+
+    #[test]
+    fn test_parse_polymer() {
+        assert_eq!(parse_polymer("aBc\n").unwrap(), vec!['a', 'B', 'c']);
+        assert_eq!(parse_polymer("").unwrap(), vec![]);
+
+        let err = parse_polymer("ab c").unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid unit ' ' at position 2");
+
+        assert!(parse_polymer("abé").is_err());
+    }
+}
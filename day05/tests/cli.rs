@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("dabAcCaCBAcCcaDA\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Reduced polymer length: 10\n\
+                     Shortest polymer length: 4 (without c/C)\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        // long polymer, reacting away except for its last unit
+        let polymer: String = "abcdefghij".repeat(5000) + &"JIHGFEDCBA".repeat(5000) + "x";
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(polymer.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Reduced polymer length: 1\n\
+                     Shortest polymer length: 0 (without x/X)\n");
+    }
+
+    #[test]
+    fn test_empty_polymer() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("\n");
+
+        assert_cmd
+            .assert()
+            .code(2)
+            .stdout("Reduced polymer length: 0\n\
+                     No unit types to remove!\n");
+    }
+
+    #[test]
+    fn test_invalid_unit() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("dabA1cC\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Invalid unit '1' at position 4. Aborting.\n");
+    }
+}
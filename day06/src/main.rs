@@ -0,0 +1,54 @@
+use std::env;
+
+use common::{get_input, parse_options, Error};
+
+mod point;
+mod regions;
+
+use crate::point::Point;
+use crate::regions::{safe_region_size, Regions};
+
+
+// total distance to all the points the safe region stays below
+const DEFAULT_THRESHOLD: u64 = 10000;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &["threshold"], &["render"])?;
+
+    let threshold = options.value_or("threshold", DEFAULT_THRESHOLD)?;
+    let points: Vec<Point> = get_input(&args)?;
+
+    let regions = Regions::new(&points)?;
+
+    // the map replaces the answers
+    if options.flag("render") {
+        print!("{}", regions.render(&points));
+        return Ok(());
+    }
+
+    // part 1
+
+    match regions.largest_finite_area() {
+        Some((index, area)) => println!("Largest finite area: {} (around {})", area, points[index]),
+        None => println!("No finite areas"),
+    }
+
+    // part 2
+
+    println!("Safe region size: {}", safe_region_size(&points, threshold)?);
+
+    Ok(())
+}
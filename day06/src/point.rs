@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str::FromStr;
+
+use common::Fail;
+
+
+pub type CoordinateType = i32;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: CoordinateType,
+    pub y: CoordinateType,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Error parsing Point from string: '{}'", string)]
+pub struct ParsePointError {
+    string: String,
+}
+
+
+impl Point {
+    pub fn distance(&self, x: i64, y: i64) -> u64 {
+        (i64::from(self.x) - x).unsigned_abs() + (i64::from(self.y) - y).unsigned_abs()
+    }
+}
+
+
+impl FromStr for Point {
+    type Err = ParsePointError;
+
+    fn from_str(s: &str) -> Result<Point, ParsePointError> {
+        let malformed = || ParsePointError { string: s.into() };
+
+        let mut coords = s.splitn(2, ',').map(|coord| coord.trim().parse::<CoordinateType>());
+
+        match (coords.next(), coords.next()) {
+            (Some(Ok(x)), Some(Ok(y))) => Ok(Point { x, y }),
+            _ => Err(malformed()),
+        }
+    }
+}
+
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.x, self.y)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Point;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("1, 6".parse::<Point>().unwrap(), Point { x: 1, y: 6 });
+        assert_eq!(" -3,10 ".parse::<Point>().unwrap(), Point { x: -3, y: 10 });
+        assert_eq!(Point { x: 8, y: 3 }.to_string(), "8, 3");
+
+        let err = "1 6".parse::<Point>().unwrap_err();
+        assert_eq!(format!("{}", err), "Error parsing Point from string: '1 6'");
+
+        assert!("1, 6, 7".parse::<Point>().is_err());
+        assert!("1,".parse::<Point>().is_err());
+        assert!("99999999999, 1".parse::<Point>().is_err());
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(Point { x: 1, y: 6 }.distance(3, 2), 6);
+        assert_eq!(Point { x: -5, y: 0 }.distance(-5, 0), 0);
+    }
+}
@@ -0,0 +1,280 @@
+use std::cmp::Reverse;
+
+use common::grid::Grid;
+use common::Fail;
+
+use crate::point::Point;
+
+
+// largest grid to compute, in cells
+const MAX_GRID_CELLS: u128 = 1 << 26;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Area of {}x{} cells is too large", width, height)]
+pub struct AreaTooLarge {
+    pub width: u128,
+    pub height: u128,
+}
+
+
+/// Rect of cells from the minimum corner (x, y).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i64,
+    pub y: i64,
+    pub width: usize,
+    pub height: usize,
+}
+
+
+/// Cells of the points' bounding box, each belonging to the closest
+/// point (in Manhattan distance), if there's only one.
+pub struct Regions {
+    bounds: Bounds,
+    // index of the closest point, `None` for ties
+    owners: Grid<Option<usize>>,
+    num_points: usize,
+}
+
+
+impl Bounds {
+    /// Smallest rect containing all the points, grown by `margin`
+    /// cells on every side.  Empty for no points.
+    pub fn around(points: &[Point], margin: u64) -> Result<Bounds, AreaTooLarge> {
+        if points.is_empty() {
+            return Ok(Bounds { x: 0, y: 0, width: 0, height: 0 });
+        }
+
+        let margin = i128::from(margin);
+
+        let min_x = points.iter().map(|point| i128::from(point.x)).min().unwrap_or(0) - margin;
+        let min_y = points.iter().map(|point| i128::from(point.y)).min().unwrap_or(0) - margin;
+        let max_x = points.iter().map(|point| i128::from(point.x)).max().unwrap_or(0) + margin;
+        let max_y = points.iter().map(|point| i128::from(point.y)).max().unwrap_or(0) + margin;
+
+        // offset the fact that we start coords at 0
+        let width = (max_x - min_x + 1) as u128;
+        let height = (max_y - min_y + 1) as u128;
+
+        match width.checked_mul(height) {
+            Some(cells) if cells <= MAX_GRID_CELLS => {},
+            _ => return Err(AreaTooLarge { width, height }),
+        }
+
+        // both within the max grid size, so the corner is within i64
+        Ok(Bounds { x: min_x as i64, y: min_y as i64, width: width as usize, height: height as usize })
+    }
+
+    /// Coordinates of the bounding box cell.
+    pub fn coords(&self, column: usize, row: usize) -> (i64, i64) {
+        (self.x + column as i64, self.y + row as i64)
+    }
+
+    pub fn is_edge(&self, column: usize, row: usize) -> bool {
+        column == 0 || row == 0 || column + 1 == self.width || row + 1 == self.height
+    }
+}
+
+
+impl Regions {
+    pub fn new(points: &[Point]) -> Result<Regions, AreaTooLarge> {
+        let bounds = Bounds::around(points, 0)?;
+
+        let owners = Grid::from_fn(bounds.width, bounds.height, |column, row| {
+            let (x, y) = bounds.coords(column, row);
+            closest_point(points, x, y)
+        });
+
+        Ok(Regions { bounds, owners, num_points: points.len() })
+    }
+
+    /// Area of each point's region, `None` for the regions reaching
+    /// the edge of the bounding box.  Moving away from the box gets
+    /// closer to the same points, so those regions go on forever.
+    pub fn areas(&self) -> Vec<Option<usize>> {
+        let mut areas = vec![Some(0); self.num_points];
+
+        for ((column, row), owner) in self.owners.iter_positions() {
+            if let Some(owner) = *owner {
+                if self.bounds.is_edge(column, row) {
+                    areas[owner] = None;
+                } else if let Some(area) = &mut areas[owner] {
+                    *area += 1;
+                }
+            }
+        }
+
+        areas
+    }
+
+    /// Index of the point with the largest finite region (the first
+    /// one on ties) and its area.
+    pub fn largest_finite_area(&self) -> Option<(usize, usize)> {
+        self.areas()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, area)| Some((index, area?)))
+            .max_by_key(|&(index, area)| (area, Reverse(index)))
+    }
+
+    /// Map of the regions, one character per cell: each point's
+    /// letter (cycling through the alphabet), capitalised where the
+    /// point itself is, and '.' for ties.
+    pub fn render(&self, points: &[Point]) -> String {
+        let mut map = self.owners.map(|owner| owner.map_or('.', region_letter));
+
+        for point in points {
+            let column = (i64::from(point.x) - self.bounds.x) as usize;
+            let row = (i64::from(point.y) - self.bounds.y) as usize;
+
+            if let Some(cell) = map.get_mut(column, row) {
+                *cell = cell.to_ascii_uppercase();
+            }
+        }
+
+        map.to_string()
+    }
+}
+
+
+fn region_letter(index: usize) -> char {
+    (b'a' + (index % 26) as u8) as char
+}
+
+
+// index of the single closest point
+fn closest_point(points: &[Point], x: i64, y: i64) -> Option<usize> {
+    let mut closest: Option<(u64, usize)> = None;
+    let mut tied = false;
+
+    for (index, point) in points.iter().enumerate() {
+        let distance = point.distance(x, y);
+
+        match closest {
+            Some((closest_distance, _)) if distance > closest_distance => {},
+            Some((closest_distance, _)) if distance == closest_distance => tied = true,
+            _ => {
+                closest = Some((distance, index));
+                tied = false;
+            },
+        }
+    }
+
+    closest.filter(|_| !tied).map(|(_, index)| index)
+}
+
+
+/// Number of cells with a total distance to all points below the
+/// threshold.  Cells outside of the bounding box are further from all
+/// the points with every step, so only the ones close enough to it are
+/// checked.
+pub fn safe_region_size(points: &[Point], threshold: u64) -> Result<usize, AreaTooLarge> {
+    if points.is_empty() {
+        return Ok(0);
+    }
+
+    // every step away from the box adds one per point to the total
+    let margin = threshold.saturating_sub(1) / points.len() as u64;
+    let bounds = Bounds::around(points, margin)?;
+
+    let mut size = 0;
+
+    for row in 0..bounds.height {
+        for column in 0..bounds.width {
+            let (x, y) = bounds.coords(column, row);
+
+            let total = points
+                .iter()
+                .try_fold(0_u64, |total, point| {
+                    Some(total + point.distance(x, y)).filter(|&total| total < threshold)
+                });
+
+            if total.is_some() {
+                size += 1;
+            }
+        }
+    }
+
+    Ok(size)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::point::Point;
+
+    use super::{safe_region_size, Bounds, Regions};
+
+    fn example_points() -> Vec<Point> {
+        [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)]
+            .iter()
+            .map(|&(x, y)| Point { x, y })
+            .collect()
+    }
+
+    #[test]
+    fn test_bounds() {
+        let points = example_points();
+
+        assert_eq!(Bounds::around(&points, 0).unwrap(), Bounds { x: 1, y: 1, width: 8, height: 9 });
+        assert_eq!(Bounds::around(&points, 2).unwrap(), Bounds { x: -1, y: -1, width: 12, height: 13 });
+        assert_eq!(Bounds::around(&[], 5).unwrap(), Bounds { x: 0, y: 0, width: 0, height: 0 });
+
+        let far = [Point { x: i32::MIN, y: 0 }, Point { x: i32::MAX, y: 0 }];
+        let err = Bounds::around(&far, 0).unwrap_err();
+        assert_eq!(format!("{}", err), "Area of 4294967296x1 cells is too large");
+    }
+
+    #[test]
+    fn test_areas() {
+        let regions = Regions::new(&example_points()).unwrap();
+
+        // only D and E are finite
+        assert_eq!(regions.areas(), vec![None, None, None, Some(9), Some(17), None]);
+        assert_eq!(regions.largest_finite_area(), Some((4, 17)));
+    }
+
+    #[test]
+    fn test_no_finite_areas() {
+        let regions = Regions::new(&[Point { x: 0, y: 0 }, Point { x: 3, y: 3 }]).unwrap();
+        assert_eq!(regions.largest_finite_area(), None);
+
+        let regions = Regions::new(&[]).unwrap();
+        assert_eq!(regions.largest_finite_area(), None);
+        assert_eq!(regions.render(&[]), "");
+    }
+
+    #[test]
+    fn test_render() {
+        let points = example_points();
+        let regions = Regions::new(&points).unwrap();
+
+        assert_eq!(
+            regions.render(&points),
+            "Aaaa.ccc\n\
+             aaddeccc\n\
+             adddeccC\n\
+             .dDdeecc\n\
+             b.deEeec\n\
+             Bb.eeee.\n\
+             bb.eeeff\n\
+             bb.eefff\n\
+             bb.ffffF\n"
+        );
+    }
+
+    #[test]
+    fn test_safe_region_size() {
+        let points = example_points();
+
+        assert_eq!(safe_region_size(&points, 32).unwrap(), 16);
+        assert_eq!(safe_region_size(&points, 0).unwrap(), 0);
+        assert_eq!(safe_region_size(&[], 32).unwrap(), 0);
+
+        // a single point's region is a diamond, reaching out of the box
+        assert_eq!(safe_region_size(&[Point { x: 0, y: 0 }], 3).unwrap(), 13);
+
+        assert!(safe_region_size(&points, u64::MAX).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the example from Advent of Code
+    const EXAMPLE_INPUT: &str = "1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--threshold", "32"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Largest finite area: 17 (around 5, 5)\n\
+                     Safe region size: 16\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        // the default threshold covers a lot more than the bounding box
+        cmd
+            .assert()
+            .success()
+            .stdout("Largest finite area: 17 (around 5, 5)\n\
+                     Safe region size: 5554416\n");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--render");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Aaaa.ccc\n\
+                     aaddeccc\n\
+                     adddeccC\n\
+                     .dDdeecc\n\
+                     b.deEeec\n\
+                     Bb.eeee.\n\
+                     bb.eeeff\n\
+                     bb.eefff\n\
+                     bb.ffffF\n");
+    }
+
+    #[test]
+    fn test_no_finite_areas() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--threshold", "4"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("0, 0\n2, 0\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("No finite areas\n\
+                     Safe region size: 3\n");
+    }
+
+    #[test]
+    fn test_invalid_point() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("1, 1\n1; 6\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Point from string: '1; 6'. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        cmd
+            .args(["--threshold", "-1"])
+            .assert()
+            .failure()
+            .stderr("Error: Invalid value for option --threshold: '-1' (invalid digit found in string). Aborting.\n");
+    }
+}
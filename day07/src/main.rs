@@ -0,0 +1,55 @@
+use std::env;
+
+use common::{get_input, parse_options, Error};
+
+mod schedule;
+mod step;
+
+use crate::schedule::{step_duration, Instructions};
+use crate::step::{Dependency, StepID};
+
+
+const DEFAULT_WORKERS: usize = 5;
+
+// seconds every step takes, on top of its letter's position
+const DEFAULT_BASE_DURATION: u32 = 60;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &["workers", "base-duration"], &[])?;
+
+    let workers = options.value_or("workers", DEFAULT_WORKERS)?;
+    let base_duration = options.value_or("base-duration", DEFAULT_BASE_DURATION)?;
+
+    let dependencies: Vec<Dependency> = get_input(&args)?;
+    let instructions = Instructions::new(&dependencies);
+
+    // part 1
+
+    println!("Step order: {}", steps_string(&instructions.order()?));
+
+    // part 2
+
+    let work = instructions.work(workers, |step| step_duration(step, base_duration))?;
+
+    println!("Done by {} workers in {} seconds: {}", workers, work.seconds, steps_string(&work.order));
+
+    Ok(())
+}
+
+
+fn steps_string(steps: &[StepID]) -> String {
+    steps.iter().collect()
+}
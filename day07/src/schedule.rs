@@ -0,0 +1,284 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::fmt;
+
+use common::Fail;
+
+use crate::step::{Dependency, StepID};
+
+
+/// Steps which depend on each other in a loop, in dependency order,
+/// with the first step repeated at the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<StepID>);
+
+
+#[derive(Debug, Fail)]
+pub enum ScheduleError {
+    #[fail(display = "Steps can never begin, they depend on each other: {}", cycle)]
+    DependencyCycle {
+        cycle: Cycle,
+    },
+
+    #[fail(display = "At least one worker is needed")]
+    NoWorkers,
+}
+
+
+/// Steps with the ones they depend on, as a graph.
+#[derive(Debug, Default)]
+pub struct Instructions {
+    // steps which must be finished first, for every step
+    prerequisites: BTreeMap<StepID, BTreeSet<StepID>>,
+    // steps waiting for each step to finish, for every step
+    dependents: BTreeMap<StepID, BTreeSet<StepID>>,
+}
+
+
+/// Steps in the order they were finished and the total time it took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Work {
+    pub order: Vec<StepID>,
+    pub seconds: u64,
+}
+
+
+// steps not finished yet, during scheduling
+struct Progress<'a> {
+    instructions: &'a Instructions,
+    // number of unfinished prerequisites, for the steps waiting for any
+    waiting: BTreeMap<StepID, usize>,
+    // steps ready to begin
+    available: BTreeSet<StepID>,
+}
+
+
+/// Time to complete the step: the base duration plus the step letter's
+/// position in the alphabet ('A' takes 1 second more, 'Z' 26 more).
+pub fn step_duration(step: StepID, base_duration: u32) -> u64 {
+    u64::from(base_duration) + u64::from(step as u32 - 'A' as u32 + 1)
+}
+
+
+impl Instructions {
+    pub fn new(dependencies: &[Dependency]) -> Instructions {
+        let mut instructions = Instructions::default();
+
+        for dependency in dependencies {
+            instructions.add_step(dependency.before).1.insert(dependency.after);
+            instructions.add_step(dependency.after).0.insert(dependency.before);
+        }
+
+        instructions
+    }
+
+    // the step's prerequisites and dependents
+    fn add_step(&mut self, step: StepID) -> (&mut BTreeSet<StepID>, &mut BTreeSet<StepID>) {
+        (self.prerequisites.entry(step).or_default(), self.dependents.entry(step).or_default())
+    }
+
+    /// Order of the steps done one by one, taking the alphabetically
+    /// first step whenever more than one is ready to begin.
+    pub fn order(&self) -> Result<Vec<StepID>, ScheduleError> {
+        // with one worker, the durations don't change the order
+        self.work(1, |_| 1).map(|work| work.order)
+    }
+
+    /// Steps done by the number of workers at once, each starting the
+    /// alphabetically first step ready to begin when idle.
+    pub fn work<F>(&self, workers: usize, duration: F) -> Result<Work, ScheduleError>
+        where F: Fn(StepID) -> u64
+    {
+        if workers == 0 {
+            return Err(ScheduleError::NoWorkers);
+        }
+
+        let mut progress = Progress::new(self);
+
+        // steps being worked on, by finishing time
+        let mut in_progress = BinaryHeap::new();
+
+        let mut order = vec![];
+        let mut seconds = 0;
+
+        loop {
+            while in_progress.len() < workers {
+                match progress.take_available() {
+                    Some(step) => in_progress.push(Reverse((seconds + duration(step), step))),
+                    None => break,
+                }
+            }
+
+            let Reverse((finish, _)) = match in_progress.peek() {
+                Some(&next) => next,
+                None => break,
+            };
+
+            seconds = finish;
+
+            // all the steps finishing at once free their dependents
+            // before any worker picks the next step
+            while let Some(&Reverse((finish, step))) = in_progress.peek() {
+                if finish != seconds {
+                    break;
+                }
+
+                in_progress.pop();
+                progress.finish(step);
+                order.push(step);
+            }
+        }
+
+        match progress.cycle() {
+            Some(cycle) => Err(ScheduleError::DependencyCycle { cycle }),
+            None => Ok(Work { order, seconds }),
+        }
+    }
+}
+
+
+impl<'a> Progress<'a> {
+    fn new(instructions: &Instructions) -> Progress<'_> {
+        let mut waiting = BTreeMap::new();
+        let mut available = BTreeSet::new();
+
+        for (&step, prerequisites) in &instructions.prerequisites {
+            if prerequisites.is_empty() {
+                available.insert(step);
+            } else {
+                waiting.insert(step, prerequisites.len());
+            }
+        }
+
+        Progress { instructions, waiting, available }
+    }
+
+    fn take_available(&mut self) -> Option<StepID> {
+        self.available.pop_first()
+    }
+
+    fn finish(&mut self, step: StepID) {
+        for dependent in &self.instructions.dependents[&step] {
+            let remaining = self.waiting.get_mut(dependent).expect("Dependent step isn't waiting");
+            *remaining -= 1;
+
+            if *remaining == 0 {
+                self.waiting.remove(dependent);
+                self.available.insert(*dependent);
+            }
+        }
+    }
+
+    // once nothing can be worked on, steps still waiting depend on
+    // each other: following unfinished prerequisites loops eventually
+    fn cycle(&self) -> Option<Cycle> {
+        let mut step = *self.waiting.keys().next()?;
+        let mut path = vec![];
+
+        loop {
+            if let Some(start) = path.iter().position(|&visited| visited == step) {
+                let mut cycle = path.split_off(start);
+                cycle.push(step);
+
+                // in dependency order, prerequisites first
+                cycle.reverse();
+
+                return Some(Cycle(cycle));
+            }
+
+            path.push(step);
+
+            step = *self.instructions.prerequisites[&step]
+                .iter()
+                .find(|prerequisite| self.waiting.contains_key(prerequisite))
+                .expect("Waiting step without unfinished prerequisites");
+        }
+    }
+}
+
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let steps: Vec<String> = self.0.iter().map(StepID::to_string).collect();
+
+        write!(f, "{}", steps.join(" -> "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::step::Dependency;
+
+    use super::{step_duration, Cycle, Instructions, ScheduleError, Work};
+
+    const EXAMPLE: &[(char, char)] = &[
+        ('C', 'A'), ('C', 'F'), ('A', 'B'), ('A', 'D'), ('B', 'E'), ('D', 'E'), ('F', 'E'),
+    ];
+
+    fn instructions(pairs: &[(char, char)]) -> Instructions {
+        let dependencies: Vec<_> = pairs
+            .iter()
+            .map(|&(before, after)| Dependency { before, after })
+            .collect();
+
+        Instructions::new(&dependencies)
+    }
+
+    fn cycle(instructions: &Instructions) -> Cycle {
+        match instructions.order() {
+            Err(ScheduleError::DependencyCycle { cycle }) => cycle,
+            other => panic!("Expected a cycle, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_order() {
+        assert_eq!(instructions(EXAMPLE).order().unwrap(), "CABDFE".chars().collect::<Vec<_>>());
+
+        // independent steps go alphabetically, repeated lines don't matter
+        let order = instructions(&[('Z', 'Y'), ('B', 'Q'), ('Z', 'Y')]).order().unwrap();
+        assert_eq!(order, vec!['B', 'Q', 'Z', 'Y']);
+
+        assert_eq!(instructions(&[]).order().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_work() {
+        let work = instructions(EXAMPLE).work(2, |step| step_duration(step, 0)).unwrap();
+        assert_eq!(work, Work { order: "CABFDE".chars().collect(), seconds: 15 });
+
+        // one worker takes the sum of all durations
+        let work = instructions(EXAMPLE).work(1, |step| step_duration(step, 60)).unwrap();
+        assert_eq!(work.seconds, 6 * 60 + 21);
+
+        // with enough workers, only the longest chain of steps counts
+        let work = instructions(EXAMPLE).work(100, |step| step_duration(step, 0)).unwrap();
+        assert_eq!(work, Work { order: "CABDFE".chars().collect(), seconds: 3 + 6 + 5 });
+    }
+
+    #[test]
+    fn test_step_duration() {
+        assert_eq!(step_duration('A', 60), 61);
+        assert_eq!(step_duration('Z', 0), 26);
+        assert_eq!(step_duration('Z', u32::MAX), u64::from(u32::MAX) + 26);
+    }
+
+    #[test]
+    fn test_cycles() {
+        assert_eq!(cycle(&instructions(&[('A', 'A')])), Cycle(vec!['A', 'A']));
+
+        // the steps before the cycle still get done
+        let looping = instructions(&[('A', 'B'), ('B', 'C'), ('C', 'D'), ('D', 'B'), ('D', 'E')]);
+        assert_eq!(cycle(&looping), Cycle(vec!['B', 'C', 'D', 'B']));
+
+        let err = looping.work(3, |_| 1).unwrap_err();
+        assert_eq!(format!("{}", err), "Steps can never begin, they depend on each other: B -> C -> D -> B");
+    }
+
+    #[test]
+    fn test_no_workers() {
+        let err = instructions(EXAMPLE).work(0, |_| 1).unwrap_err();
+        assert_eq!(format!("{}", err), "At least one worker is needed");
+    }
+}
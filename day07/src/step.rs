@@ -0,0 +1,84 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use common::Fail;
+
+
+/// Steps are named with a single capital letter.
+pub type StepID = char;
+
+
+/// Line of the instructions, e.g.
+/// "Step C must be finished before step A can begin."
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dependency {
+    pub before: StepID,
+    pub after: StepID,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Error parsing Dependency from string: '{}'", string)]
+pub struct ParseDependencyError {
+    string: String,
+}
+
+
+impl FromStr for Dependency {
+    type Err = ParseDependencyError;
+
+    fn from_str(s: &str) -> Result<Dependency, ParseDependencyError> {
+        lazy_static! {
+            static ref PATTERN: Regex = Regex::new(
+                r"^Step (?P<before>[A-Z]) must be finished before step (?P<after>[A-Z]) can begin\.$"
+            ).expect("Invalid regex pattern");
+        }
+
+        let caps = PATTERN
+            .captures(s.trim())
+            .ok_or_else(|| ParseDependencyError { string: s.into() })?;
+
+        // the pattern only matches single letters
+        let step = |name: &str| caps[name].chars().next().expect("Empty step name");
+
+        Ok(Dependency { before: step("before"), after: step("after") })
+    }
+}
+
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Step {} must be finished before step {} can begin.", self.before, self.after)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Dependency;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "Step C must be finished before step A can begin.".parse::<Dependency>().unwrap(),
+            Dependency { before: 'C', after: 'A' }
+        );
+
+        let line = "Step F must be finished before step E can begin.";
+        assert_eq!(line.parse::<Dependency>().unwrap().to_string(), line);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "Step c must be finished before step A can begin.".parse::<Dependency>().unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Error parsing Dependency from string: 'Step c must be finished before step A can begin.'");
+
+        assert!("Step CA must be finished before step A can begin.".parse::<Dependency>().is_err());
+        assert!("Step C must be finished before step A can begin".parse::<Dependency>().is_err());
+        assert!("".parse::<Dependency>().is_err());
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the example from Advent of Code
+    const EXAMPLE_INPUT: &str = "\
+        Step C must be finished before step A can begin.\n\
+        Step C must be finished before step F can begin.\n\
+        Step A must be finished before step B can begin.\n\
+        Step A must be finished before step D can begin.\n\
+        Step B must be finished before step E can begin.\n\
+        Step D must be finished before step E can begin.\n\
+        Step F must be finished before step E can begin.\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--workers", "2", "--base-duration", "0"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Step order: CABDFE\n\
+                     Done by 2 workers in 15 seconds: CABFDE\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        // default workers and base duration
+        cmd
+            .assert()
+            .success()
+            .stdout("Step order: CABDFE\n\
+                     Done by 5 workers in 253 seconds: CAFBDE\n");
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(
+            "Step A must be finished before step B can begin.\n\
+             Step B must be finished before step A can begin.\n"
+        );
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Steps can never begin, they depend on each other: A -> B -> A. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_dependency() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("Step C must be finished before step A can begin.\nStep C\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Dependency from string: 'Step C'. Aborting.\n");
+    }
+
+    #[test]
+    fn test_no_workers() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--workers", "0"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stdout("Step order: CABDFE\n")
+            .stderr("Reading input from stdin.\n\
+                     Error: At least one worker is needed. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_base_duration() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        cmd
+            .args(["--base-duration", "x"])
+            .assert()
+            .failure()
+            .stderr("Error: Invalid value for option --base-duration: 'x' (invalid digit found in string). Aborting.\n");
+    }
+}
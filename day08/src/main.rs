@@ -0,0 +1,33 @@
+use std::env;
+
+use common::{get_input_text, Error};
+
+use day08::tree::Tree;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    // a single line of numbers, one tree
+    let tree: Tree = get_input_text(args)?.parse()?;
+
+    // part 1
+
+    println!("Metadata sum: {}", tree.metadata_sum());
+
+    // part 2
+
+    println!("Root value: {}", tree.root_value()?);
+
+    Ok(())
+}
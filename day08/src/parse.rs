@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::tree::{NodeData, NodeID, Tree};
+
+
+#[derive(Debug, Fail)]
+pub enum ParseTreeError {
+    #[fail(display = "Invalid number '{}' at offset {}", string, offset)]
+    InvalidNumber {
+        string: String,
+        offset: usize,
+    },
+
+    #[fail(display = "Input ends at offset {} in the middle of node {}", offset, node)]
+    Truncated {
+        offset: usize,
+        node: NodeID,
+    },
+
+    #[fail(display = "Unexpected data at offset {} after the root node", offset)]
+    TrailingData {
+        offset: usize,
+    },
+}
+
+
+// whitespace separated numbers, with their byte offsets
+struct Numbers<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+
+// node with its header read, until all its children are
+struct Unfinished {
+    id: NodeID,
+    children_left: usize,
+    metadata_len: usize,
+}
+
+
+impl<'a> Numbers<'a> {
+    fn new(input: &'a str) -> Numbers<'a> {
+        Numbers { input, position: 0 }
+    }
+
+    // the next number in the node, which the input must have
+    fn read<T: FromStr>(&mut self, node: NodeID) -> Result<T, ParseTreeError> {
+        let (offset, string) = self
+            .next()
+            .ok_or(ParseTreeError::Truncated { offset: self.input.len(), node })?;
+
+        string
+            .parse()
+            .map_err(|_| ParseTreeError::InvalidNumber { string: string.into(), offset })
+    }
+}
+
+
+impl<'a> Iterator for Numbers<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let rest = &self.input[self.position..];
+
+        let start = self.position + rest.find(|c: char| !c.is_whitespace())?;
+        let end = self.input[start..]
+            .find(char::is_whitespace)
+            .map_or(self.input.len(), |len| start + len);
+
+        self.position = end;
+
+        Some((start, &self.input[start..end]))
+    }
+}
+
+
+// reads the node's header, adding it to the tree
+fn begin_node(numbers: &mut Numbers, tree: &mut Tree) -> Result<Unfinished, ParseTreeError> {
+    let id = tree.nodes.len();
+
+    let children_left = numbers.read(id)?;
+    let metadata_len = numbers.read(id)?;
+
+    tree.nodes.push(NodeData { children: vec![], metadata: 0..0 });
+
+    Ok(Unfinished { id, children_left, metadata_len })
+}
+
+
+impl FromStr for Tree {
+    type Err = ParseTreeError;
+
+    /// Nodes given as the number of child nodes, the number of metadata
+    /// entries, the child nodes and the metadata entries, e.g.
+    /// "1 1 0 1 99 2" for a node with one child.
+    ///
+    /// Unfinished nodes are kept on a stack instead of recursing, so
+    /// the depth of the tree is only limited by memory.
+    fn from_str(s: &str) -> Result<Tree, ParseTreeError> {
+        let mut tree = Tree { nodes: vec![], metadata: vec![] };
+        let mut numbers = Numbers::new(s);
+
+        let mut unfinished = vec![begin_node(&mut numbers, &mut tree)?];
+
+        while let Some(node) = unfinished.last_mut() {
+            if node.children_left > 0 {
+                node.children_left -= 1;
+                let parent = node.id;
+
+                let child = begin_node(&mut numbers, &mut tree)?;
+                tree.nodes[parent].children.push(child.id);
+                unfinished.push(child);
+
+                continue;
+            }
+
+            let start = tree.metadata.len();
+
+            for _ in 0..node.metadata_len {
+                let entry = numbers.read(node.id)?;
+                tree.metadata.push(entry);
+            }
+
+            tree.nodes[node.id].metadata = start..tree.metadata.len();
+            unfinished.pop();
+        }
+
+        match numbers.next() {
+            Some((offset, _)) => Err(ParseTreeError::TrailingData { offset }),
+            None => Ok(tree),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::Tree;
+
+    fn error(input: &str) -> String {
+        input.parse::<Tree>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_parse() {
+        let tree: Tree = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2".parse().unwrap();
+
+        let metadata: Vec<_> = tree.depth_first().map(|node| node.metadata().to_vec()).collect();
+        assert_eq!(metadata, vec![vec![1, 1, 2], vec![10, 11, 12], vec![2], vec![99]]);
+
+        // any whitespace, including a trailing newline
+        let same: Tree = "2 3\n0 3 10 11 12\t1 1  0 1 99 2\n1 1 2\n".parse().unwrap();
+        assert_eq!(same, tree);
+
+        let tree: Tree = "0 0".parse().unwrap();
+        assert_eq!(tree.len(), 1);
+        assert!(tree.root().metadata().is_empty());
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(error(""), "Input ends at offset 0 in the middle of node 0");
+        assert_eq!(error("  \n"), "Input ends at offset 3 in the middle of node 0");
+        assert_eq!(error("1"), "Input ends at offset 1 in the middle of node 0");
+        assert_eq!(error("1 1 0 2 5"), "Input ends at offset 9 in the middle of node 1");
+        assert_eq!(error("1 1 0 2 5 6"), "Input ends at offset 11 in the middle of node 0");
+
+        // more children than the input could ever have
+        assert_eq!(error("4000000000 0 0 0"), "Input ends at offset 16 in the middle of node 2");
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        assert_eq!(error("0 1 5 0"), "Unexpected data at offset 6 after the root node");
+        assert_eq!(error("0 0\n0 0\n"), "Unexpected data at offset 4 after the root node");
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        assert_eq!(error("0 1 x"), "Invalid number 'x' at offset 4");
+        assert_eq!(error("0 -1"), "Invalid number '-1' at offset 2");
+        assert_eq!(error("0 1 4294967296"), "Invalid number '4294967296' at offset 4");
+        assert_eq!(error("0,1 5"), "Invalid number '0,1' at offset 0");
+    }
+}
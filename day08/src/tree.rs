@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use common::Fail;
+
+
+pub type MetadataType = u32;
+
+/// Index of a node in the tree, in the order nodes appear in the input.
+/// The root is always 0.
+pub type NodeID = usize;
+
+
+/// Tree of nodes, each with child nodes and metadata entries.
+///
+/// Nodes are stored flat, in the order they appear in the input (so
+/// every child comes after its parent), which keeps even very deep
+/// trees from needing recursion to traverse or drop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    pub(crate) nodes: Vec<NodeData>,
+    pub(crate) metadata: Vec<MetadataType>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NodeData {
+    pub(crate) children: Vec<NodeID>,
+    // entries in the tree's metadata
+    pub(crate) metadata: Range<usize>,
+}
+
+
+/// Node of a tree, borrowed from it.
+#[derive(Debug, Clone, Copy)]
+pub struct Node<'a> {
+    tree: &'a Tree,
+    id: NodeID,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Value of node {} is too large", node)]
+pub struct ValueOverflow {
+    pub node: NodeID,
+}
+
+
+/// Nodes in depth-first order: each node before its children, which
+/// come in order.  This is the order of the input.
+pub struct DepthFirst<'a> {
+    tree: &'a Tree,
+    stack: Vec<NodeID>,
+}
+
+
+/// Nodes in breadth-first order: level by level from the root.
+pub struct BreadthFirst<'a> {
+    tree: &'a Tree,
+    queue: VecDeque<NodeID>,
+}
+
+
+impl Tree {
+    pub fn root(&self) -> Node<'_> {
+        self.node(0).expect("Tree without a root")
+    }
+
+    pub fn node(&self, id: NodeID) -> Option<Node<'_>> {
+        if id < self.nodes.len() {
+            Some(Node { tree: self, id })
+        } else {
+            None
+        }
+    }
+
+    /// Number of nodes, at least 1.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // a tree always has a root
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst { tree: self, stack: vec![0] }
+    }
+
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst { tree: self, queue: vec![0].into() }
+    }
+
+    /// Sum of the metadata entries of all nodes.
+    pub fn metadata_sum(&self) -> u64 {
+        self.metadata.iter().map(|&entry| u64::from(entry)).sum()
+    }
+
+    /// Value of every node, by ID.  A node without children is worth
+    /// the sum of its metadata entries.  Otherwise the entries refer to
+    /// its children (1 being the first one) and the node is worth the
+    /// sum of the values of those children, with entries not referring
+    /// to any child skipped.
+    pub fn values(&self) -> Result<Vec<u64>, ValueOverflow> {
+        let mut values = vec![0; self.nodes.len()];
+
+        // children come after their parents, so going backwards every
+        // child's value is known before its parent's
+        for (id, node) in self.nodes.iter().enumerate().rev() {
+            let metadata = &self.metadata[node.metadata.clone()];
+
+            let value = if node.children.is_empty() {
+                metadata
+                    .iter()
+                    .try_fold(0_u64, |sum, &entry| sum.checked_add(u64::from(entry)))
+            } else {
+                metadata
+                    .iter()
+                    .filter_map(|&entry| (entry as usize).checked_sub(1))
+                    .filter_map(|index| node.children.get(index))
+                    .try_fold(0_u64, |sum, &child| sum.checked_add(values[child]))
+            };
+
+            values[id] = value.ok_or(ValueOverflow { node: id })?;
+        }
+
+        Ok(values)
+    }
+
+    pub fn root_value(&self) -> Result<u64, ValueOverflow> {
+        Ok(self.values()?[0])
+    }
+}
+
+
+impl<'a> Node<'a> {
+    pub fn id(&self) -> NodeID {
+        self.id
+    }
+
+    pub fn children(&self) -> impl ExactSizeIterator<Item = Node<'a>> + 'a {
+        let tree = self.tree;
+
+        self.data().children.iter().map(move |&id| Node { tree, id })
+    }
+
+    /// Child by its index (0 being the first child).
+    pub fn child(&self, index: usize) -> Option<Node<'a>> {
+        self.data().children.get(index).map(|&id| Node { tree: self.tree, id })
+    }
+
+    pub fn metadata(&self) -> &'a [MetadataType] {
+        &self.tree.metadata[self.data().metadata.clone()]
+    }
+
+    fn data(&self) -> &'a NodeData {
+        &self.tree.nodes[self.id]
+    }
+}
+
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let id = self.stack.pop()?;
+
+        // first child on the top
+        self.stack.extend(self.tree.nodes[id].children.iter().rev());
+
+        Some(Node { tree: self.tree, id })
+    }
+}
+
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let id = self.queue.pop_front()?;
+
+        self.queue.extend(&self.tree.nodes[id].children);
+
+        Some(Node { tree: self.tree, id })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Tree;
+
+    const EXAMPLE: &str = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2";
+
+    fn ids<'a>(nodes: impl Iterator<Item = super::Node<'a>>) -> Vec<usize> {
+        nodes.map(|node| node.id()).collect()
+    }
+
+    #[test]
+    fn test_nodes() {
+        let tree: Tree = EXAMPLE.parse().unwrap();
+        let root = tree.root();
+
+        assert_eq!(tree.len(), 4);
+        assert_eq!(root.id(), 0);
+        assert_eq!(root.metadata(), &[1, 1, 2]);
+        assert_eq!(ids(root.children()), vec![1, 2]);
+
+        let c = root.child(1).unwrap();
+        assert_eq!(c.metadata(), &[2]);
+        assert_eq!(c.child(0).unwrap().metadata(), &[99]);
+        assert!(c.child(1).is_none());
+
+        assert!(tree.node(4).is_none());
+    }
+
+    #[test]
+    fn test_traversal() {
+        let tree: Tree = "3 1 0 1 1 2 1 0 1 3 0 1 4 2 0 1 5 6".parse().unwrap();
+
+        // 0 has children 1, 2 and 5; 2 has 3 and 4
+        assert_eq!(ids(tree.depth_first()), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(ids(tree.breadth_first()), vec![0, 1, 2, 5, 3, 4]);
+
+        let metadata: Vec<_> = tree.breadth_first().flat_map(|node| node.metadata().to_vec()).collect();
+        assert_eq!(metadata, vec![6, 1, 2, 5, 3, 4]);
+    }
+
+    #[test]
+    fn test_metadata_sum() {
+        let tree: Tree = EXAMPLE.parse().unwrap();
+        assert_eq!(tree.metadata_sum(), 138);
+
+        let tree: Tree = "0 2 4294967295 4294967295".parse().unwrap();
+        assert_eq!(tree.metadata_sum(), 2 * 4294967295);
+    }
+
+    #[test]
+    fn test_values() {
+        let tree: Tree = EXAMPLE.parse().unwrap();
+
+        // D is worth 99, so C (referring to a missing child) is worth 0
+        assert_eq!(tree.values().unwrap(), vec![66, 33, 0, 99]);
+        assert_eq!(tree.root_value().unwrap(), 66);
+
+        // entry 0 doesn't refer to any child
+        let tree: Tree = "1 2 0 1 7 0 1".parse().unwrap();
+        assert_eq!(tree.root_value().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_value_overflow() {
+        // every level doubles the value of the one below
+        let depth = 70;
+        let input = format!("{}0 1 1{}", "1 2 ".repeat(depth), " 1 1".repeat(depth));
+
+        let tree: Tree = input.parse().unwrap();
+
+        let err = tree.root_value().unwrap_err();
+        assert_eq!(format!("{}", err), "Value of node 6 is too large");
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let depth = 1_000_000;
+        let input = format!("{}0 1 1{}", "1 1 ".repeat(depth), " 1".repeat(depth));
+
+        let tree: Tree = input.parse().unwrap();
+
+        assert_eq!(tree.len(), depth + 1);
+        assert_eq!(tree.metadata_sum(), depth as u64 + 1);
+        assert_eq!(tree.root_value().unwrap(), 1);
+        assert_eq!(tree.depth_first().count(), depth + 1);
+        assert_eq!(tree.breadth_first().last().unwrap().metadata(), &[1]);
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the example from Advent of Code
+    const EXAMPLE_INPUT: &str = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Metadata sum: 138\n\
+                     Root value: 66\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Metadata sum: 138\n\
+                     Root value: 66\n");
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        // far deeper than the call stack would allow recursing
+        let depth = 500_000;
+        let input = format!("{}0 1 7{}\n", "1 1 ".repeat(depth), " 1".repeat(depth));
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(input);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Metadata sum: 500007\n\
+                     Root value: 7\n");
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Input ends at offset 34 in the middle of node 0. Aborting.\n");
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2 3\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Unexpected data at offset 36 after the root node. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("2 3 0 3 10 eleven 12\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Invalid number 'eleven' at offset 11. Aborting.\n");
+    }
+}
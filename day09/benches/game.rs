@@ -0,0 +1,41 @@
+// the example games, and a puzzle sized one at part 2's 100x marbles
+// (which should take well under a second)
+//
+//     cargo bench -p day09
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day09::game::{winner, Game};
+
+
+fn bench_game(c: &mut Criterion) {
+    let mut group = c.benchmark_group("game");
+    group.sample_size(10);
+
+    let example = Game { players: 30, last_marble: 5807 };
+
+    group.bench_function("example", |b| {
+        b.iter(|| winner(&black_box(example).scores().unwrap()))
+    });
+
+    // puzzle inputs have a few hundred players and ~70000 marbles
+    let puzzle = Game { players: 430, last_marble: 71588 };
+
+    group.bench_function("puzzle", |b| {
+        b.iter(|| winner(&black_box(puzzle).scores().unwrap()))
+    });
+
+    let longer = puzzle.longer(100).unwrap();
+
+    group.bench_function("puzzle_100x", |b| {
+        b.iter(|| winner(&black_box(longer).scores().unwrap()))
+    });
+
+    group.finish();
+}
+
+
+criterion_group!(benches, bench_game);
+criterion_main!(benches);
@@ -0,0 +1,214 @@
+use std::fmt;
+
+use common::Fail;
+
+
+pub type Marble = u32;
+
+// largest last marble to make room for, taking 512 MiB of links
+pub const MAX_MARBLES: Marble = 1 << 26;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Too many marbles: the last one is worth {} points, at most {} supported", last_marble, max)]
+pub struct TooManyMarbles {
+    pub last_marble: u64,
+    pub max: Marble,
+}
+
+
+/// Marbles in a circle, with one of them current.
+///
+/// The circle is a doubly linked list stored in two arrays indexed by
+/// marble, since every marble is placed at most once: inserting and
+/// removing next to the current marble never moves any others.
+#[derive(Debug, Clone)]
+pub struct Circle {
+    // marble clockwise of each marble
+    next: Vec<Marble>,
+    // marble anticlockwise of each marble
+    prev: Vec<Marble>,
+    current: Marble,
+    len: usize,
+    // marble 0 is never scored in the game, but could be removed
+    has_zero: bool,
+}
+
+
+impl Circle {
+    /// Circle of the single marble 0, with room for marbles up to
+    /// `last_marble`.
+    pub fn new(last_marble: Marble) -> Result<Circle, TooManyMarbles> {
+        if last_marble > MAX_MARBLES {
+            return Err(TooManyMarbles { last_marble: u64::from(last_marble), max: MAX_MARBLES });
+        }
+
+        let slots = last_marble as usize + 1;
+
+        // marble 0 is linked to itself, the rest isn't placed yet
+        let next = vec![0; slots];
+        let prev = vec![0; slots];
+
+        Ok(Circle { next, prev, current: 0, len: 1, has_zero: true })
+    }
+
+    pub fn current(&self) -> Marble {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // there's always the current marble
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn rotate_clockwise(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.current = self.next[self.current as usize];
+        }
+    }
+
+    pub fn rotate_anticlockwise(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.current = self.prev[self.current as usize];
+        }
+    }
+
+    /// Place the marble clockwise of the current one, making it the
+    /// current marble.
+    ///
+    /// Panics if the marble is beyond the circle's capacity.
+    pub fn insert(&mut self, marble: Marble) {
+        let before = self.current;
+        let after = self.next[before as usize];
+
+        self.next[marble as usize] = after;
+        self.prev[marble as usize] = before;
+        self.next[before as usize] = marble;
+        self.prev[after as usize] = marble;
+
+        self.current = marble;
+        self.len += 1;
+    }
+
+    /// Take out the current marble, the one clockwise of it becoming
+    /// current.  `None` if it's the last marble left.
+    pub fn remove(&mut self) -> Option<Marble> {
+        if self.len == 1 {
+            return None;
+        }
+
+        let removed = self.current;
+        let before = self.prev[removed as usize];
+        let after = self.next[removed as usize];
+
+        self.next[before as usize] = after;
+        self.prev[after as usize] = before;
+
+        self.current = after;
+        self.len -= 1;
+        self.has_zero &= removed != 0;
+
+        Some(removed)
+    }
+
+    /// Marbles clockwise, starting from marble 0 if it's still there,
+    /// or from the current one otherwise.
+    pub fn iter(&self) -> impl Iterator<Item = Marble> + '_ {
+        let mut marble = if self.has_zero { 0 } else { self.current };
+
+        (0..self.len).map(move |_| {
+            let this = marble;
+            marble = self.next[marble as usize];
+            this
+        })
+    }
+}
+
+
+/// Marbles from 0 clockwise, the current one in parentheses, as in the
+/// puzzle, e.g. "0 (2) 1".
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let marbles: Vec<String> = self
+            .iter()
+            .map(|marble| {
+                if marble == self.current {
+                    format!("({})", marble)
+                } else {
+                    marble.to_string()
+                }
+            })
+            .collect();
+
+        write!(f, "{}", marbles.join(" "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Circle, MAX_MARBLES};
+
+    #[test]
+    fn test_insert() {
+        let mut circle = Circle::new(3).unwrap();
+        assert_eq!(circle.to_string(), "(0)");
+
+        circle.insert(1);
+        circle.rotate_clockwise(1);
+        circle.insert(2);
+        circle.rotate_clockwise(1);
+        circle.insert(3);
+
+        assert_eq!(circle.to_string(), "0 2 1 (3)");
+        assert_eq!(circle.len(), 4);
+        assert_eq!(circle.current(), 3);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut circle = Circle::new(3).unwrap();
+        circle.insert(1);
+        circle.insert(2);
+        circle.insert(3);
+
+        // one full turn and a bit
+        circle.rotate_clockwise(5);
+        assert_eq!(circle.to_string(), "(0) 1 2 3");
+
+        circle.rotate_anticlockwise(2);
+        assert_eq!(circle.to_string(), "0 1 (2) 3");
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut circle = Circle::new(3).unwrap();
+        circle.insert(1);
+        circle.insert(2);
+        circle.insert(3);
+        circle.rotate_anticlockwise(1);
+
+        assert_eq!(circle.remove(), Some(2));
+        assert_eq!(circle.to_string(), "0 1 (3)");
+
+        // removing marble 0 starts the listing from the current marble
+        assert_eq!(circle.remove(), Some(3));
+        assert_eq!(circle.remove(), Some(0));
+        assert_eq!(circle.to_string(), "(1)");
+        assert_eq!(circle.remove(), None);
+        assert_eq!(circle.len(), 1);
+    }
+
+    #[test]
+    fn test_too_many_marbles() {
+        assert!(Circle::new(MAX_MARBLES).is_ok());
+
+        let err = Circle::new(MAX_MARBLES + 1).unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Too many marbles: the last one is worth 67108865 points, at most 67108864 supported");
+    }
+}
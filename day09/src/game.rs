@@ -0,0 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use common::Fail;
+
+use crate::circle::{Circle, Marble, TooManyMarbles, MAX_MARBLES};
+
+
+// marbles divisible by this are scored instead of placed
+const SCORING_MARBLE: Marble = 23;
+
+// how far anticlockwise the marble scored with it is
+const SCORED_OFFSET: usize = 7;
+
+// most players to keep scores for, taking 8 MiB
+pub const MAX_PLAYERS: usize = 1 << 20;
+
+
+/// Game settings, e.g. "10 players; last marble is worth 1618 points".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Game {
+    pub players: usize,
+    pub last_marble: Marble,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ParseGameError {
+    #[fail(display = "Error parsing Game from string: '{}'", string)]
+    MalformedString {
+        string: String,
+    },
+
+    #[fail(display = "Error parsing Game from string '{}': there must be at least one player", string)]
+    NoPlayers {
+        string: String,
+    },
+
+    #[fail(display = "Error parsing Game from string '{}': at most {} players are supported", string, max)]
+    TooManyPlayers {
+        string: String,
+        max: usize,
+    },
+}
+
+
+/// Player with the highest score, numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Winner {
+    pub player: usize,
+    pub score: u64,
+}
+
+
+impl Game {
+    /// The same game, with the last marble worth `factor` times more.
+    pub fn longer(&self, factor: Marble) -> Result<Game, TooManyMarbles> {
+        let last_marble = u64::from(self.last_marble) * u64::from(factor);
+
+        if last_marble > u64::from(MAX_MARBLES) {
+            return Err(TooManyMarbles { last_marble, max: MAX_MARBLES });
+        }
+
+        Ok(Game { last_marble: last_marble as Marble, ..*self })
+    }
+
+    /// Final score of every player, in the order they take turns.
+    pub fn scores(&self) -> Result<Vec<u64>, TooManyMarbles> {
+        self.play(|_| {})
+    }
+
+    /// Like `scores`, calling `observe` with the circle after every
+    /// turn.
+    pub fn play<F>(&self, mut observe: F) -> Result<Vec<u64>, TooManyMarbles>
+        where F: FnMut(&Circle)
+    {
+        let mut circle = Circle::new(self.last_marble)?;
+        let mut scores = vec![0; self.players];
+
+        for (marble, player) in (1..=self.last_marble).zip((0..self.players).cycle()) {
+            if marble % SCORING_MARBLE == 0 {
+                circle.rotate_anticlockwise(SCORED_OFFSET);
+
+                // there are always more than 7 marbles by the first
+                // scoring one
+                let scored = circle.remove().expect("Removing the only marble");
+
+                scores[player] += u64::from(marble) + u64::from(scored);
+            } else {
+                circle.rotate_clockwise(1);
+                circle.insert(marble);
+            }
+
+            observe(&circle);
+        }
+
+        Ok(scores)
+    }
+}
+
+
+/// The highest score, going to the first player on ties.  `None` for no
+/// players.
+pub fn winner(scores: &[u64]) -> Option<Winner> {
+    let (index, &score) = scores
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|&(_, score)| score)?;
+
+    Some(Winner { player: index + 1, score })
+}
+
+
+impl FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Game, ParseGameError> {
+        lazy_static! {
+            static ref PATTERN: Regex = Regex::new(
+                r"^(?P<players>\d+) players?; last marble is worth (?P<last_marble>\d+) points?$"
+            ).expect("Invalid regex pattern");
+        }
+
+        let malformed = || ParseGameError::MalformedString { string: s.into() };
+
+        let caps = PATTERN.captures(s.trim()).ok_or_else(malformed)?;
+
+        let players = caps["players"].parse().map_err(|_| malformed())?;
+        let last_marble = caps["last_marble"].parse().map_err(|_| malformed())?;
+
+        if players == 0 {
+            return Err(ParseGameError::NoPlayers { string: s.into() });
+        }
+
+        if players > MAX_PLAYERS {
+            return Err(ParseGameError::TooManyPlayers { string: s.into(), max: MAX_PLAYERS });
+        }
+
+        Ok(Game { players, last_marble })
+    }
+}
+
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} players; last marble is worth {} points", self.players, self.last_marble)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{winner, Game, Winner};
+
+    fn high_score(players: usize, last_marble: u32) -> u64 {
+        winner(&Game { players, last_marble }.scores().unwrap()).unwrap().score
+    }
+
+    #[test]
+    fn test_parse() {
+        let game: Game = "10 players; last marble is worth 1618 points".parse().unwrap();
+        assert_eq!(game, Game { players: 10, last_marble: 1618 });
+        assert_eq!(game.to_string(), "10 players; last marble is worth 1618 points");
+
+        assert_eq!("1 player; last marble is worth 1 point\n".parse::<Game>().unwrap(),
+                   Game { players: 1, last_marble: 1 });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "10 players; last marble is 1618".parse::<Game>().unwrap_err();
+        assert_eq!(format!("{}", err), "Error parsing Game from string: '10 players; last marble is 1618'");
+
+        let err = "0 players; last marble is worth 25 points".parse::<Game>().unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Error parsing Game from string '0 players; last marble is worth 25 points': \
+                    there must be at least one player");
+
+        assert!("9 players; last marble is worth 99999999999 points".parse::<Game>().is_err());
+
+        let err = "99999999999999999 players; last marble is worth 25 points".parse::<Game>().unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Error parsing Game from string '99999999999999999 players; last marble is worth 25 points': \
+                    at most 1048576 players are supported");
+
+        assert!("1048576 players; last marble is worth 25 points".parse::<Game>().is_ok());
+    }
+
+    #[test]
+    fn test_example_turns() {
+        let mut circles = vec![];
+        let scores = Game { players: 9, last_marble: 25 }.play(|circle| circles.push(circle.to_string())).unwrap();
+
+        assert_eq!(circles[0], "0 (1)");
+        assert_eq!(circles[3], "0 (4) 2 1 3");
+        assert_eq!(circles[21], "0 16 8 17 4 18 9 19 2 20 10 21 5 (22) 11 1 12 6 13 3 14 7 15");
+        assert_eq!(circles[22], "0 16 8 17 4 18 (19) 2 20 10 21 5 22 11 1 12 6 13 3 14 7 15");
+        assert_eq!(circles[24], "0 16 8 17 4 18 19 2 24 20 (25) 10 21 5 22 11 1 12 6 13 3 14 7 15");
+
+        // marble 23 went to the fifth player, with 9 from the circle
+        assert_eq!(winner(&scores), Some(Winner { player: 5, score: 32 }));
+    }
+
+    #[test]
+    fn test_high_scores() {
+        assert_eq!(high_score(10, 1618), 8317);
+        assert_eq!(high_score(13, 7999), 146373);
+        assert_eq!(high_score(17, 1104), 2764);
+        assert_eq!(high_score(21, 6111), 54718);
+        assert_eq!(high_score(30, 5807), 37305);
+    }
+
+    #[test]
+    fn test_no_scores() {
+        // nobody gets to score before marble 23
+        let scores = Game { players: 3, last_marble: 22 }.scores().unwrap();
+        assert_eq!(scores, vec![0, 0, 0]);
+        assert_eq!(winner(&scores), Some(Winner { player: 1, score: 0 }));
+
+        assert_eq!(Game { players: 1, last_marble: 0 }.scores().unwrap(), vec![0]);
+        assert_eq!(winner(&[]), None);
+    }
+
+    #[test]
+    fn test_longer() {
+        let game = Game { players: 10, last_marble: 1618 };
+        assert_eq!(game.longer(100).unwrap(), Game { players: 10, last_marble: 161800 });
+
+        // right at the limit, and just past it
+        assert!(Game { players: 10, last_marble: 671_088 }.longer(100).is_ok());
+
+        let err = Game { players: 10, last_marble: 671_089 }.longer(100).unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "Too many marbles: the last one is worth 67108900 points, at most 67108864 supported");
+
+        // would overflow the marble type
+        let err = Game { players: 10, last_marble: u32::MAX / 2 }.longer(100).unwrap_err();
+        assert_eq!(err.last_marble, 214_748_364_700);
+    }
+
+    #[test]
+    fn test_too_many_marbles() {
+        let err = Game { players: 1, last_marble: 42_949_672 * 2 }.scores().unwrap_err();
+        assert_eq!(err.last_marble, 85_899_344);
+    }
+}
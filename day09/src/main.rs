@@ -0,0 +1,58 @@
+use std::env;
+
+use common::{get_input_text, parse_options, Error};
+
+use day09::game::{winner, Game};
+
+
+// part 2 is the same game with a last marble worth this many times more
+const LONGER_GAME_FACTOR: u32 = 100;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &[], &["scores"])?;
+
+    let game: Game = get_input_text(&args)?.trim().parse()?;
+
+    // checked before spending any time on part 1
+    let longer_game = game.longer(LONGER_GAME_FACTOR)?;
+
+    // part 1
+
+    report("High score", &game.scores()?, options.flag("scores"));
+
+    // part 2
+
+    report(&format!("High score with {}x marbles", LONGER_GAME_FACTOR),
+           &longer_game.scores()?,
+           options.flag("scores"));
+
+    Ok(())
+}
+
+
+// the winner, then every player's score if asked for
+fn report(title: &str, scores: &[u64], all_scores: bool) {
+    // parsing makes sure there are players
+    if let Some(winner) = winner(scores) {
+        println!("{}: {} (player {})", title, winner.score, winner.player);
+    }
+
+    if all_scores {
+        for (index, score) in scores.iter().enumerate() {
+            println!("    player {}: {}", index + 1, score);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the first example from Advent of Code
+    const EXAMPLE_INPUT: &str = "10 players; last marble is worth 1618 points\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("High score: 8317 (player 10)\n\
+                     High score with 100x marbles: 74765078 (player 2)\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("High score: 8317 (player 10)\n\
+                     High score with 100x marbles: 74765078 (player 2)\n");
+    }
+
+    #[test]
+    fn test_all_scores() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--scores");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("3 players; last marble is worth 25 points\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("High score: 32 (player 2)\n\
+                     \x20   player 1: 0\n\
+                     \x20   player 2: 32\n\
+                     \x20   player 3: 0\n\
+                     High score with 100x marbles: 61201 (player 3)\n\
+                     \x20   player 1: 58327\n\
+                     \x20   player 2: 57773\n\
+                     \x20   player 3: 61201\n");
+    }
+
+    #[test]
+    fn test_no_players() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("0 players; last marble is worth 25 points\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Game from string '0 players; last marble is worth 25 points': \
+                     there must be at least one player. Aborting.\n");
+    }
+
+    #[test]
+    fn test_too_many_players() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("99999999999999999 players; last marble is worth 25 points\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Game from string '99999999999999999 players; last marble is worth 25 points': \
+                     at most 1048576 players are supported. Aborting.\n");
+    }
+
+    #[test]
+    fn test_too_many_marbles() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("1 player; last marble is worth 42949672 points\n");
+
+        // part 2 would need tens of GiB
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Too many marbles: the last one is worth 4294967200 points, \
+                     at most 67108864 supported. Aborting.\n");
+    }
+}
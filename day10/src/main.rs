@@ -0,0 +1,41 @@
+use std::env;
+
+use common::{get_input, Error};
+
+mod ocr;
+mod sky;
+mod star;
+
+use crate::ocr::read_message;
+use crate::sky::find_message;
+use crate::star::Star;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let stars: Vec<Star> = get_input(args)?;
+
+    let message = find_message(&stars)?;
+
+    // part 1
+
+    println!("Message: {}", read_message(&message.sky));
+    print!("{}", message.sky);
+
+    // part 2
+
+    println!("Seconds to wait: {}", message.seconds);
+
+    Ok(())
+}
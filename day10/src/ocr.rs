@@ -0,0 +1,153 @@
+use common::grid::Grid;
+
+use crate::sky::LIT;
+
+
+/// Stands in for letters not in any font.
+pub const UNKNOWN_LETTER: char = '?';
+
+
+// letters of the same height, each as rows of lit ('#') and dark ('.')
+// cells, exactly as wide as the letter
+type Font = &'static [(char, &'static [&'static str])];
+
+
+// the letters of puzzle inputs, 10 cells high
+const LARGE_FONT: Font = &[
+    ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', &["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', &[".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', &[".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', &["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', &["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', &["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', &["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', &["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', &["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+
+// the letters of the puzzle's example, 8 cells high
+const EXAMPLE_FONT: Font = &[
+    ('H', &["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#", "#...#"]),
+    ('I', &["###", ".#.", ".#.", ".#.", ".#.", ".#.", ".#.", "###"]),
+];
+
+
+const FONTS: &[Font] = &[LARGE_FONT, EXAMPLE_FONT];
+
+
+/// Letters spelled by the lit cells of the sky, left to right.  Letters
+/// are told apart by the dark columns between them and looked up in
+/// built-in fonts of the sky's height.
+pub fn read_message(sky: &Grid<char>) -> String {
+    letter_columns(sky)
+        .into_iter()
+        .map(|(start, end)| recognise(sky, start, end))
+        .collect()
+}
+
+
+// runs of columns with any lit cells, as (start, end) exclusive
+fn letter_columns(sky: &Grid<char>) -> Vec<(usize, usize)> {
+    let mut letters = vec![];
+    let mut start = None;
+
+    for x in 0..=sky.width() {
+        let lit = x < sky.width() && sky.column(x).any(|&cell| cell == LIT);
+
+        match (start, lit) {
+            (None, true) => start = Some(x),
+            (Some(letter_start), false) => {
+                letters.push((letter_start, x));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    letters
+}
+
+
+fn recognise(sky: &Grid<char>, start: usize, end: usize) -> char {
+    let matches = |rows: &[&str]| {
+        rows.len() == sky.height()
+            && rows
+                .iter()
+                .enumerate()
+                .all(|(y, row)| row.len() == end - start && row.chars().eq(sky.row(y)[start..end].iter().cloned()))
+    };
+
+    FONTS
+        .iter()
+        .flat_map(|font| font.iter())
+        .find(|(_, rows)| matches(rows))
+        .map_or(UNKNOWN_LETTER, |&(letter, _)| letter)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use common::grid::Grid;
+
+    use super::{read_message, FONTS};
+
+    // letters of the font next to each other, two dark columns apart
+    fn spell(font: &[(char, &[&str])], text: &str) -> Grid<char> {
+        let glyphs: Vec<_> = text
+            .chars()
+            .map(|letter| font.iter().find(|(glyph, _)| *glyph == letter).unwrap().1)
+            .collect();
+
+        let rows: Vec<String> = (0..glyphs[0].len())
+            .map(|y| glyphs.iter().map(|glyph| glyph[y]).collect::<Vec<_>>().join(".."))
+            .collect();
+
+        rows.join("\n").parse().unwrap()
+    }
+
+    #[test]
+    fn test_fonts() {
+        for font in FONTS {
+            let height = font[0].1.len();
+
+            for &(letter, rows) in font.iter() {
+                assert_eq!(rows.len(), height, "{} is the wrong height", letter);
+                assert!(rows.iter().all(|row| row.len() == rows[0].len()), "{} is ragged", letter);
+
+                // no dark columns inside or around a letter
+                let glyph: Grid<char> = rows.join("\n").parse().unwrap();
+                assert!((0..glyph.width()).all(|x| glyph.column(x).any(|&cell| cell == '#')),
+                        "{} has a dark column", letter);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_message() {
+        let all_letters: String = FONTS[0].iter().map(|&(letter, _)| letter).collect();
+        assert_eq!(read_message(&spell(FONTS[0], &all_letters)), all_letters);
+
+        assert_eq!(read_message(&spell(FONTS[1], "HIH")), "HIH");
+    }
+
+    #[test]
+    fn test_unknown_letters() {
+        let sky: Grid<char> = "#..#.##\n#..#.#.\n".parse().unwrap();
+        assert_eq!(read_message(&sky), "???");
+
+        // a row too many for any font
+        let mut sky = spell(FONTS[0], "HAH");
+        let extra_row = Grid::filled(sky.width(), 1, '.');
+        sky = format!("{}{}", sky, extra_row).parse().unwrap();
+        assert_eq!(read_message(&sky), "???");
+
+        assert_eq!(read_message(&Grid::filled(3, 3, '.')), "");
+    }
+}
@@ -0,0 +1,241 @@
+use common::grid::Grid;
+use common::Fail;
+
+use crate::star::Star;
+
+
+pub const LIT: char = '#';
+pub const DARK: char = '.';
+
+// messages are a line of letters, anything larger is just scattered stars
+const MAX_MESSAGE_WIDTH: u128 = 1000;
+const MAX_MESSAGE_HEIGHT: u128 = 50;
+
+
+#[derive(Debug, Fail)]
+pub enum SkyError {
+    #[fail(display = "No stars in the sky")]
+    NoStars,
+
+    #[fail(display = "Stars are still coming together after {} seconds", seconds)]
+    NeverConverges {
+        seconds: u32,
+    },
+
+    #[fail(display = "Stars are closest after {} seconds, but spread over {}x{}, too large for a message",
+           seconds, width, height)]
+    NoMessage {
+        seconds: u32,
+        width: u128,
+        height: u128,
+    },
+}
+
+
+/// Smallest rect containing all the stars, from the minimum corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i64,
+    pub y: i64,
+    pub width: u128,
+    pub height: u128,
+}
+
+
+/// Stars rendered when they're closest together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub seconds: u32,
+    pub sky: Grid<char>,
+}
+
+
+/// Bounds of the stars after the number of seconds, `None` for no
+/// stars.
+pub fn bounds(stars: &[Star], seconds: u32) -> Option<Bounds> {
+    let positions: Vec<_> = stars.iter().map(|star| star.position_at(seconds)).collect();
+
+    let min_x = positions.iter().map(|&(x, _)| x).min()?;
+    let min_y = positions.iter().map(|&(_, y)| y).min()?;
+    let max_x = positions.iter().map(|&(x, _)| x).max()?;
+    let max_y = positions.iter().map(|&(_, y)| y).max()?;
+
+    // offset the fact that we start coords at 0
+    Some(Bounds {
+        x: min_x,
+        y: min_y,
+        width: (i128::from(max_x) - i128::from(min_x) + 1) as u128,
+        height: (i128::from(max_y) - i128::from(min_y) + 1) as u128,
+    })
+}
+
+
+// half the perimeter of the bounds
+fn extent(stars: &[Star], seconds: u32) -> u128 {
+    bounds(stars, seconds).map_or(0, |bounds| bounds.width + bounds.height)
+}
+
+
+/// Number of seconds after which the stars are closest together, the
+/// earliest one if they stay that close for a while.
+///
+/// Every star moves in a straight line, so the extent of their bounds
+/// only ever shrinks, then grows: the moment it stops shrinking is
+/// binary searched for, instead of moving the stars second by second.
+pub fn converge(stars: &[Star]) -> Result<u32, SkyError> {
+    if stars.is_empty() {
+        return Err(SkyError::NoStars);
+    }
+
+    let stops_shrinking = |seconds: u32| {
+        seconds == u32::MAX || extent(stars, seconds + 1) >= extent(stars, seconds)
+    };
+
+    if extent(stars, u32::MAX) < extent(stars, u32::MAX - 1) {
+        return Err(SkyError::NeverConverges { seconds: u32::MAX });
+    }
+
+    let (mut low, mut high) = (0, u32::MAX);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        if stops_shrinking(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Ok(low)
+}
+
+
+/// The sky after the number of seconds, from the stars' bounds, with
+/// lit and dark cells.
+pub fn render(stars: &[Star], seconds: u32) -> Result<Grid<char>, SkyError> {
+    let bounds = bounds(stars, seconds).ok_or(SkyError::NoStars)?;
+
+    if bounds.width > MAX_MESSAGE_WIDTH || bounds.height > MAX_MESSAGE_HEIGHT {
+        return Err(SkyError::NoMessage { seconds, width: bounds.width, height: bounds.height });
+    }
+
+    let mut sky = Grid::filled(bounds.width as usize, bounds.height as usize, DARK);
+
+    for star in stars {
+        let (x, y) = star.position_at(seconds);
+
+        sky[((x - bounds.x) as usize, (y - bounds.y) as usize)] = LIT;
+    }
+
+    Ok(sky)
+}
+
+
+/// The sky at the moment the stars are closest together.
+pub fn find_message(stars: &[Star]) -> Result<Message, SkyError> {
+    let seconds = converge(stars)?;
+
+    Ok(Message { seconds, sky: render(stars, seconds)? })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::star::Star;
+
+    use super::{bounds, converge, find_message, render, Bounds};
+
+    const EXAMPLE: &str = "\
+        position=< 9,  1> velocity=< 0,  2>\n\
+        position=< 7,  0> velocity=<-1,  0>\n\
+        position=< 3, -2> velocity=<-1,  1>\n\
+        position=< 6, 10> velocity=<-2, -1>\n\
+        position=< 2, -4> velocity=< 2,  2>\n\
+        position=<-6, 10> velocity=< 2, -2>\n\
+        position=< 1,  8> velocity=< 1, -1>\n\
+        position=< 1,  7> velocity=< 1,  0>\n\
+        position=<-3, 11> velocity=< 1, -2>\n\
+        position=< 7,  6> velocity=<-1, -1>\n\
+        position=<-2,  3> velocity=< 1,  0>\n\
+        position=<-4,  3> velocity=< 2,  0>\n\
+        position=<10, -3> velocity=<-1,  1>\n\
+        position=< 5, 11> velocity=< 1, -2>\n\
+        position=< 4,  7> velocity=< 0, -1>\n\
+        position=< 8, -2> velocity=< 0,  1>\n\
+        position=<15,  0> velocity=<-2,  0>\n\
+        position=< 1,  6> velocity=< 1,  0>\n\
+        position=< 8,  9> velocity=< 0, -1>\n\
+        position=< 3,  3> velocity=<-1,  1>\n\
+        position=< 0,  5> velocity=< 0, -1>\n\
+        position=<-2,  2> velocity=< 2,  0>\n\
+        position=< 5, -2> velocity=< 1,  2>\n\
+        position=< 1,  4> velocity=< 2,  1>\n\
+        position=<-2,  7> velocity=< 2, -2>\n\
+        position=< 3,  6> velocity=<-1, -1>\n\
+        position=< 5,  0> velocity=< 1,  0>\n\
+        position=<-6,  0> velocity=< 2,  0>\n\
+        position=< 5,  9> velocity=< 1, -2>\n\
+        position=<14,  7> velocity=<-2,  0>\n\
+        position=<-3,  6> velocity=< 2, -1>\n";
+
+    fn example_stars() -> Vec<Star> {
+        EXAMPLE.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_bounds() {
+        let stars = example_stars();
+
+        assert_eq!(bounds(&stars, 0), Some(Bounds { x: -6, y: -4, width: 22, height: 16 }));
+        assert_eq!(bounds(&stars, 3), Some(Bounds { x: 0, y: 0, width: 10, height: 8 }));
+        assert_eq!(bounds(&[], 3), None);
+    }
+
+    #[test]
+    fn test_find_message() {
+        let message = find_message(&example_stars()).unwrap();
+
+        assert_eq!(message.seconds, 3);
+        assert_eq!(
+            message.sky.to_string(),
+            "#...#..###\n\
+             #...#...#.\n\
+             #...#...#.\n\
+             #####...#.\n\
+             #...#...#.\n\
+             #...#...#.\n\
+             #...#...#.\n\
+             #...#..###\n"
+        );
+    }
+
+    #[test]
+    fn test_still_stars() {
+        // not moving or all moving together, the sky never changes
+        let still = [Star { position: (0, 0), velocity: (0, 0) }, Star { position: (2, 1), velocity: (0, 0) }];
+        assert_eq!(converge(&still).unwrap(), 0);
+
+        let drifting = [Star { position: (0, 0), velocity: (3, 1) }, Star { position: (2, 1), velocity: (3, 1) }];
+        assert_eq!(converge(&drifting).unwrap(), 0);
+        assert_eq!(render(&drifting, 0).unwrap().to_string(), "#..\n..#\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(format!("{}", converge(&[]).unwrap_err()), "No stars in the sky");
+
+        // flying apart from the start
+        let scattering = [Star { position: (0, 0), velocity: (-1, 0) }, Star { position: (2000, 0), velocity: (1, 0) }];
+        assert_eq!(format!("{}", find_message(&scattering).unwrap_err()),
+                   "Stars are closest after 0 seconds, but spread over 2001x1, too large for a message");
+
+        // far too slow to ever meet
+        let crawling = [
+            Star { position: (i32::MIN, 0), velocity: (0, 0) },
+            Star { position: (i32::MAX, 0), velocity: (-1, 0) },
+        ];
+        assert_eq!(format!("{}", converge(&crawling).unwrap_err()),
+                   "Stars are still coming together after 4294967295 seconds");
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use common::Fail;
+
+
+pub type CoordinateType = i32;
+
+
+/// Point of light moving at a constant velocity, e.g.
+/// "position=< 9,  1> velocity=< 0,  2>".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Star {
+    pub position: (CoordinateType, CoordinateType),
+    pub velocity: (CoordinateType, CoordinateType),
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Error parsing Star from string: '{}'", string)]
+pub struct ParseStarError {
+    string: String,
+}
+
+
+impl Star {
+    /// Position after the number of seconds.  Wide enough not to
+    /// overflow for any number of seconds fitting in 32 bits.
+    pub fn position_at(&self, seconds: u32) -> (i64, i64) {
+        let seconds = i64::from(seconds);
+
+        (i64::from(self.position.0) + i64::from(self.velocity.0) * seconds,
+         i64::from(self.position.1) + i64::from(self.velocity.1) * seconds)
+    }
+}
+
+
+impl FromStr for Star {
+    type Err = ParseStarError;
+
+    fn from_str(s: &str) -> Result<Star, ParseStarError> {
+        lazy_static! {
+            static ref PATTERN: Regex = Regex::new(
+                r"^position=<\s*(?P<x>-?\d+),\s*(?P<y>-?\d+)>\s*velocity=<\s*(?P<dx>-?\d+),\s*(?P<dy>-?\d+)>$"
+            ).expect("Invalid regex pattern");
+        }
+
+        let malformed = || ParseStarError { string: s.into() };
+
+        let caps = PATTERN.captures(s.trim()).ok_or_else(malformed)?;
+
+        let number = |name: &str| caps[name].parse().map_err(|_| malformed());
+
+        Ok(Star {
+            position: (number("x")?, number("y")?),
+            velocity: (number("dx")?, number("dy")?),
+        })
+    }
+}
+
+
+impl fmt::Display for Star {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "position=<{}, {}> velocity=<{}, {}>",
+               self.position.0, self.position.1, self.velocity.0, self.velocity.1)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Star;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "position=< 9,  1> velocity=< 0,  2>".parse::<Star>().unwrap(),
+            Star { position: (9, 1), velocity: (0, 2) }
+        );
+
+        assert_eq!(
+            "position=<-54217, -21587> velocity=< 5,  2>".parse::<Star>().unwrap(),
+            Star { position: (-54217, -21587), velocity: (5, 2) }
+        );
+
+        let err = "position=< 9,  1>".parse::<Star>().unwrap_err();
+        assert_eq!(format!("{}", err), "Error parsing Star from string: 'position=< 9,  1>'");
+
+        assert!("position=< 9,  1> velocity=< 0, 99999999999>".parse::<Star>().is_err());
+        assert!("position=< 9.5,  1> velocity=< 0, 2>".parse::<Star>().is_err());
+    }
+
+    #[test]
+    fn test_position_at() {
+        let star = Star { position: (3, -2), velocity: (-1, 1) };
+
+        assert_eq!(star.position_at(0), (3, -2));
+        assert_eq!(star.position_at(3), (0, 1));
+
+        let fast = Star { position: (i32::MIN, 0), velocity: (i32::MIN, i32::MAX) };
+        assert_eq!(fast.position_at(u32::MAX), (i64::from(i32::MIN) * (1 + i64::from(u32::MAX)),
+                                                i64::from(i32::MAX) * i64::from(u32::MAX)));
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    // the example from Advent of Code
+    const EXAMPLE_INPUT: &str = "\
+        position=< 9,  1> velocity=< 0,  2>\n\
+        position=< 7,  0> velocity=<-1,  0>\n\
+        position=< 3, -2> velocity=<-1,  1>\n\
+        position=< 6, 10> velocity=<-2, -1>\n\
+        position=< 2, -4> velocity=< 2,  2>\n\
+        position=<-6, 10> velocity=< 2, -2>\n\
+        position=< 1,  8> velocity=< 1, -1>\n\
+        position=< 1,  7> velocity=< 1,  0>\n\
+        position=<-3, 11> velocity=< 1, -2>\n\
+        position=< 7,  6> velocity=<-1, -1>\n\
+        position=<-2,  3> velocity=< 1,  0>\n\
+        position=<-4,  3> velocity=< 2,  0>\n\
+        position=<10, -3> velocity=<-1,  1>\n\
+        position=< 5, 11> velocity=< 1, -2>\n\
+        position=< 4,  7> velocity=< 0, -1>\n\
+        position=< 8, -2> velocity=< 0,  1>\n\
+        position=<15,  0> velocity=<-2,  0>\n\
+        position=< 1,  6> velocity=< 1,  0>\n\
+        position=< 8,  9> velocity=< 0, -1>\n\
+        position=< 3,  3> velocity=<-1,  1>\n\
+        position=< 0,  5> velocity=< 0, -1>\n\
+        position=<-2,  2> velocity=< 2,  0>\n\
+        position=< 5, -2> velocity=< 1,  2>\n\
+        position=< 1,  4> velocity=< 2,  1>\n\
+        position=<-2,  7> velocity=< 2, -2>\n\
+        position=< 3,  6> velocity=<-1, -1>\n\
+        position=< 5,  0> velocity=< 1,  0>\n\
+        position=<-6,  0> velocity=< 2,  0>\n\
+        position=< 5,  9> velocity=< 1, -2>\n\
+        position=<14,  7> velocity=<-2,  0>\n\
+        position=<-3,  6> velocity=< 2, -1>\n";
+
+    const EXAMPLE_OUTPUT: &str = "\
+        Message: HI\n\
+        #...#..###\n\
+        #...#...#.\n\
+        #...#...#.\n\
+        #####...#.\n\
+        #...#...#.\n\
+        #...#...#.\n\
+        #...#...#.\n\
+        #...#..###\n\
+        Seconds to wait: 3\n";
+
+    // a message in the letters of puzzle inputs
+    const LARGE_MESSAGE: &str = "\
+        .####.....##....#####.\n\
+        #....#...#..#...#....#\n\
+        #.......#....#..#....#\n\
+        #.......#....#..#....#\n\
+        #.......#....#..#####.\n\
+        #.......######..#....#\n\
+        #.......#....#..#....#\n\
+        #.......#....#..#....#\n\
+        #....#..#....#..#....#\n\
+        .####...#....#..#####.\n";
+
+    // stars coming from all directions to light up the message
+    fn stars_spelling(message: &str, seconds: i32) -> String {
+        let velocities = [(1, 0), (-2, 1), (0, -3), (3, 3), (-1, -1), (2, -4)];
+        let mut stars = String::new();
+
+        for (y, row) in message.lines().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    let (dx, dy) = velocities[(x * 7 + y) % velocities.len()];
+                    let (x, y) = (x as i32 + 500, y as i32 - 300);
+
+                    stars.push_str(&format!("position=<{}, {}> velocity=<{}, {}>\n",
+                                            x - dx * seconds, y - dy * seconds, dx, dy));
+                }
+            }
+        }
+
+        stars
+    }
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE_INPUT);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout(EXAMPLE_OUTPUT);
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE_INPUT.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout(EXAMPLE_OUTPUT);
+    }
+
+    #[test]
+    fn test_large_message() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(stars_spelling(LARGE_MESSAGE, 10391));
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout(format!("Message: CAB\n{}Seconds to wait: 10391\n", LARGE_MESSAGE));
+    }
+
+    #[test]
+    fn test_no_message() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("position=<0, 0> velocity=<-1, 0>\n\
+                                               position=<2000, 0> velocity=<1, 0>\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Stars are closest after 0 seconds, but spread over 2001x1, \
+                     too large for a message. Aborting.\n");
+    }
+
+    #[test]
+    fn test_no_stars() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: No stars in the sky. Aborting.\n");
+    }
+
+    #[test]
+    fn test_invalid_star() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("position=< 9,  1> velocity=< 0,  2>\nposition=< 9,  1>\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Star from string: 'position=< 9,  1>'. Aborting.\n");
+    }
+}
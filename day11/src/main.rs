@@ -0,0 +1,52 @@
+use std::env;
+
+use common::{get_input_value, parse_options, Error};
+
+mod power;
+
+use crate::power::{PowerGrid, SerialNumber};
+
+
+const DEFAULT_GRID_SIZE: usize = 300;
+
+// size of the squares in part 1
+const SQUARE_SIZE: usize = 3;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &["grid-size"], &[])?;
+
+    let grid_size = options.value_or("grid-size", DEFAULT_GRID_SIZE)?;
+    let serial_number: SerialNumber = get_input_value(&args)?;
+
+    let grid = PowerGrid::new(grid_size, serial_number)?;
+
+    // part 1
+
+    match grid.best_square(SQUARE_SIZE) {
+        Some(square) => println!("Best {0}x{0} square: {1},{2} (total power {3})",
+                                 SQUARE_SIZE, square.x, square.y, square.power),
+        None => println!("No {0}x{0} squares in the grid", SQUARE_SIZE),
+    }
+
+    // part 2
+
+    match grid.best_square_any_size() {
+        Some(square) => println!("Best square: {} (total power {})", square, square.power),
+        None => println!("No squares in the grid"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use common::summed_area::SummedArea;
+use common::Fail;
+
+
+pub type SerialNumber = i32;
+
+// largest grid to compute, in cells per side
+const MAX_GRID_SIZE: usize = 4096;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Grid of {0}x{0} fuel cells is too large", size)]
+pub struct GridTooLarge {
+    pub size: usize,
+}
+
+
+/// Square of fuel cells, from its top left cell (x, y), counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Square {
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+    pub power: i64,
+}
+
+
+/// Square grid of fuel cells, with the total power of any square of
+/// them available in constant time.
+pub struct PowerGrid {
+    size: usize,
+    sums: SummedArea<i64>,
+}
+
+
+/// Power level of the fuel cell at (x, y), counted from 1: the hundreds
+/// digit of ((x + 10) * y + serial number) * (x + 10), minus 5.  Only
+/// the magnitude counts for negative numbers.
+pub fn power_level(x: usize, y: usize, serial_number: SerialNumber) -> i64 {
+    let rack_id = x as i64 + 10;
+    let power = (rack_id * y as i64 + i64::from(serial_number)) * rack_id;
+
+    (power.abs() / 100) % 10 - 5
+}
+
+
+impl PowerGrid {
+    pub fn new(size: usize, serial_number: SerialNumber) -> Result<PowerGrid, GridTooLarge> {
+        if size > MAX_GRID_SIZE {
+            return Err(GridTooLarge { size });
+        }
+
+        let sums = SummedArea::new(size, size, |x, y| power_level(x + 1, y + 1, serial_number));
+
+        Ok(PowerGrid { size, sums })
+    }
+
+    /// Total power of the square of the size from (x, y), counted from
+    /// 1.  Panics if the square doesn't fit.
+    pub fn square_power(&self, x: usize, y: usize, size: usize) -> i64 {
+        self.sums.sum(x - 1, y - 1, size, size)
+    }
+
+    /// The square of the size with the most total power, the first one
+    /// in reading order on ties.  `None` if no square that size fits.
+    pub fn best_square(&self, size: usize) -> Option<Square> {
+        if size == 0 || size > self.size {
+            return None;
+        }
+
+        let positions = self.size - size + 1;
+        let mut best: Option<Square> = None;
+
+        for y in 1..=positions {
+            for x in 1..=positions {
+                let power = self.square_power(x, y, size);
+
+                if best.is_none_or(|best| power > best.power) {
+                    best = Some(Square { x, y, size, power });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The square of any size with the most total power, the smallest
+    /// one on ties.  Each square's total takes constant time, so this
+    /// is O(n³) for a grid n cells across.
+    pub fn best_square_any_size(&self) -> Option<Square> {
+        let mut best: Option<Square> = None;
+
+        for size in 1..=self.size {
+            let square = self.best_square(size)?;
+
+            if best.is_none_or(|best| square.power > best.power) {
+                best = Some(square);
+            }
+        }
+
+        best
+    }
+}
+
+
+/// Puzzle answer format, "x,y,size".
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.size)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{power_level, PowerGrid, Square};
+
+    #[test]
+    fn test_power_level() {
+        assert_eq!(power_level(3, 5, 8), 4);
+        assert_eq!(power_level(122, 79, 57), -5);
+        assert_eq!(power_level(217, 196, 39), 0);
+        assert_eq!(power_level(101, 153, 71), 4);
+
+        // ((10 * 1) - 1234) * 10 = -12240
+        assert_eq!(power_level(0, 1, -1234), -3);
+    }
+
+    #[test]
+    fn test_square_power() {
+        let grid = PowerGrid::new(300, 18).unwrap();
+
+        assert_eq!(grid.square_power(33, 45, 3), 29);
+        assert_eq!(grid.square_power(33, 45, 1), power_level(33, 45, 18));
+        assert_eq!(grid.square_power(1, 1, 300), (1..=300)
+            .flat_map(|y| (1..=300).map(move |x| power_level(x, y, 18)))
+            .sum::<i64>());
+    }
+
+    #[test]
+    fn test_best_square() {
+        let grid = PowerGrid::new(300, 18).unwrap();
+        assert_eq!(grid.best_square(3), Some(Square { x: 33, y: 45, size: 3, power: 29 }));
+
+        let grid = PowerGrid::new(300, 42).unwrap();
+        assert_eq!(grid.best_square(3), Some(Square { x: 21, y: 61, size: 3, power: 30 }));
+
+        assert_eq!(grid.best_square(0), None);
+        assert_eq!(grid.best_square(301), None);
+        assert_eq!(grid.best_square(300).map(|square| (square.x, square.y)), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_best_square_any_size() {
+        let square = PowerGrid::new(300, 18).unwrap().best_square_any_size().unwrap();
+        assert_eq!(square, Square { x: 90, y: 269, size: 16, power: 113 });
+        assert_eq!(square.to_string(), "90,269,16");
+
+        let square = PowerGrid::new(300, 42).unwrap().best_square_any_size().unwrap();
+        assert_eq!(square, Square { x: 232, y: 251, size: 12, power: 119 });
+    }
+
+    #[test]
+    fn test_grid_sizes() {
+        // a 1x1 grid only has its one cell
+        let grid = PowerGrid::new(1, 8).unwrap();
+        assert_eq!(grid.best_square_any_size(), Some(Square { x: 1, y: 1, size: 1, power: power_level(1, 1, 8) }));
+
+        let grid = PowerGrid::new(0, 8).unwrap();
+        assert_eq!(grid.best_square(3), None);
+        assert_eq!(grid.best_square_any_size(), None);
+
+        let err = PowerGrid::new(5000, 8).err().unwrap();
+        assert_eq!(format!("{}", err), "Grid of 5000x5000 fuel cells is too large");
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("18\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Best 3x3 square: 33,45 (total power 29)\n\
+                     Best square: 90,269,16 (total power 113)\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(b"42").unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Best 3x3 square: 21,61 (total power 30)\n\
+                     Best square: 232,251,12 (total power 119)\n");
+    }
+
+    #[test]
+    fn test_grid_size() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--grid-size", "50"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("18\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Best 3x3 square: 33,45 (total power 29)\n\
+                     Best square: 35,32,13 (total power 47)\n");
+    }
+
+    #[test]
+    fn test_small_grid() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--grid-size", "2"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("18\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("No 3x3 squares in the grid\n\
+                     Best square: 2,2,1 (total power 0)\n");
+    }
+
+    #[test]
+    fn test_invalid_serial_number() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("18 42\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Invalid input value: '18 42' (invalid digit found in string). Aborting.\n");
+    }
+
+    #[test]
+    fn test_grid_too_large() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--grid-size", "100000"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("18\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Grid of 100000x100000 fuel cells is too large. Aborting.\n");
+    }
+}
@@ -0,0 +1,47 @@
+use std::env;
+
+use common::{get_input_text, parse_options, Error};
+
+mod notes;
+mod pots;
+mod rules;
+
+use crate::notes::Notes;
+
+
+// generations of the two parts, unless given
+const GENERATIONS: [u64; 2] = [20, 50_000_000_000];
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &["generations"], &[])?;
+
+    let generations = match options.value("generations")? {
+        Some(generations) => vec![generations],
+        None => GENERATIONS.to_vec(),
+    };
+
+    let notes: Notes = get_input_text(&args)?.parse()?;
+
+    // part 1 and part 2
+
+    for generations in generations {
+        let sum = notes.initial_state.sum_after(&notes.rules, generations)?;
+
+        println!("Sum of pots with plants after {} generations: {}", generations, sum);
+    }
+
+    Ok(())
+}
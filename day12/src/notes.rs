@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::pots::{ParsePotsError, Pots};
+use crate::rules::{ParseRuleError, Rule, Rules, RulesError};
+
+
+const INITIAL_STATE_PREFIX: &str = "initial state: ";
+
+
+/// The puzzle input: the initial state of the pots, a blank line, then
+/// the rules one per line.
+#[derive(Debug, Clone)]
+pub struct Notes {
+    pub initial_state: Pots,
+    pub rules: Rules,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum ParseNotesError {
+    #[fail(display = "Missing the initial state line")]
+    MissingInitialState,
+
+    #[fail(display = "{}", _0)]
+    InvalidInitialState(ParsePotsError),
+
+    #[fail(display = "{}", _0)]
+    InvalidRule(ParseRuleError),
+
+    #[fail(display = "{}", _0)]
+    InvalidRules(RulesError),
+}
+
+
+impl FromStr for Notes {
+    type Err = ParseNotesError;
+
+    fn from_str(s: &str) -> Result<Notes, ParseNotesError> {
+        let mut lines = s.lines();
+
+        let initial_state = lines
+            .next()
+            .and_then(|line| line.strip_prefix(INITIAL_STATE_PREFIX))
+            .ok_or(ParseNotesError::MissingInitialState)?
+            .parse()
+            .map_err(ParseNotesError::InvalidInitialState)?;
+
+        // the blank line, or any others
+        let rules: Vec<Rule> = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(ParseNotesError::InvalidRule)?;
+
+        let rules = Rules::new(&rules).map_err(ParseNotesError::InvalidRules)?;
+
+        Ok(Notes { initial_state, rules })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Notes;
+
+    #[test]
+    fn test_parse() {
+        let notes: Notes = "initial state: #..#.#..##\n\n...## => #\n..#.. => #\n.#.#. => .\n".parse().unwrap();
+
+        assert_eq!(notes.initial_state.to_string(), "#..#.#..##");
+        assert!(notes.rules.grows(0b00011));
+        assert!(!notes.rules.grows(0b01010));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |text: &str| text.parse::<Notes>().unwrap_err().to_string();
+
+        assert_eq!(error(""), "Missing the initial state line");
+        assert_eq!(error("#..#.#..##\n\n...## => #\n"), "Missing the initial state line");
+        assert_eq!(error("initial state: #..#.o..##\n"), "Error parsing Pots from string: '#..#.o..##'");
+        assert_eq!(error("initial state: #\n\n...## => #\n...#\n"), "Error parsing Rule from string: '...#'");
+        assert_eq!(error("initial state: #\n\n..... => #\n"), "Plants would grow in infinitely many empty pots");
+    }
+}
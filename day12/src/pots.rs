@@ -0,0 +1,248 @@
+use std::fmt;
+use std::str::FromStr;
+
+use common::Fail;
+
+use crate::rules::{parse_pot, pot_char, Rules, WINDOW};
+
+
+// generations simulated while waiting for a steady pattern, at most
+const MAX_SIMULATED_GENERATIONS: u64 = 10_000;
+
+
+/// Row of pots, infinite both ways, only keeping track of the ones
+/// from the first to the last plant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pots {
+    // number of the first pot kept, the first plant unless empty,
+    // else 0
+    first: i64,
+    plants: Vec<bool>,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Error parsing Pots from string: '{}'", string)]
+pub struct ParsePotsError {
+    string: String,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Plants don't settle into a steady pattern within {} generations", generations)]
+pub struct NoSteadyState {
+    pub generations: u64,
+}
+
+
+impl Pots {
+    /// Pots from number `first` on.
+    pub fn new(first: i64, plants: Vec<bool>) -> Pots {
+        let mut pots = Pots { first, plants };
+        pots.trim();
+
+        pots
+    }
+
+    fn trim(&mut self) {
+        match self.plants.iter().position(|&plant| plant) {
+            Some(start) => {
+                let end = self.plants.iter().rposition(|&plant| plant).map_or(0, |end| end + 1);
+
+                self.plants.truncate(end);
+                self.plants.drain(..start);
+                self.first += start as i64;
+            },
+            None => {
+                self.plants.clear();
+                self.first = 0;
+            },
+        }
+    }
+
+    pub fn num_plants(&self) -> usize {
+        self.plants.iter().filter(|&&plant| plant).count()
+    }
+
+    /// Sum of the numbers of the pots with plants.
+    pub fn sum(&self) -> i128 {
+        self.plants
+            .iter()
+            .zip(self.first..)
+            .filter(|(&plant, _)| plant)
+            .map(|(_, number)| i128::from(number))
+            .sum()
+    }
+
+    /// The next generation.  Plants can only spread to the two pots
+    /// either side of the current ones.
+    pub fn next(&self, rules: &Rules) -> Pots {
+        let reach = WINDOW / 2;
+        let mut plants = Vec::with_capacity(self.plants.len() + 2 * reach);
+
+        // window of pots, the last one entering on the right, centred
+        // `reach` pots before it
+        let mut window = 0;
+
+        for index in 0..self.plants.len() + 2 * reach {
+            let entering = self.plants.get(index).cloned().unwrap_or(false);
+            window = (window << 1 | entering as usize) & ((1 << WINDOW) - 1);
+
+            plants.push(rules.grows(window));
+        }
+
+        Pots::new(self.first - reach as i64, plants)
+    }
+
+    /// Sum of the pot numbers with plants after the number of
+    /// generations.
+    ///
+    /// Plants usually settle into the same pattern moving along the
+    /// pots at a constant speed, or staying put.  Once they do, every
+    /// further generation just adds the same amount, so the sum is
+    /// extrapolated instead of simulating every generation.
+    pub fn sum_after(&self, rules: &Rules, generations: u64) -> Result<i128, NoSteadyState> {
+        let mut pots = self.clone();
+
+        for generation in 1..=generations {
+            let next = pots.next(rules);
+
+            if next.plants == pots.plants {
+                let shift = i128::from(next.first - pots.first);
+                let remaining = i128::from(generations - generation);
+
+                return Ok(next.sum() + shift * remaining * next.num_plants() as i128);
+            }
+
+            if generation == MAX_SIMULATED_GENERATIONS && generations > generation {
+                return Err(NoSteadyState { generations: MAX_SIMULATED_GENERATIONS });
+            }
+
+            pots = next;
+        }
+
+        Ok(pots.sum())
+    }
+}
+
+
+/// Pots from number 0 on, e.g. "#..#.#".
+impl FromStr for Pots {
+    type Err = ParsePotsError;
+
+    fn from_str(s: &str) -> Result<Pots, ParsePotsError> {
+        let plants = s
+            .trim()
+            .chars()
+            .map(parse_pot)
+            .collect::<Option<_>>()
+            .ok_or_else(|| ParsePotsError { string: s.into() })?;
+
+        Ok(Pots::new(0, plants))
+    }
+}
+
+
+/// Pots from the first to the last plant.
+impl fmt::Display for Pots {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pots: String = self.plants.iter().map(|&plant| pot_char(plant)).collect();
+
+        write!(f, "{}", pots)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::{Rule, Rules};
+
+    use super::Pots;
+
+    const EXAMPLE_RULES: &[&str] = &[
+        "...## => #", "..#.. => #", ".#... => #", ".#.#. => #", ".#.## => #", ".##.. => #", ".#### => #",
+        "#.#.# => #", "#.### => #", "##.#. => #", "##.## => #", "###.. => #", "###.# => #", "####. => #",
+    ];
+
+    fn rules(lines: &[&str]) -> Rules {
+        let rules: Vec<Rule> = lines.iter().map(|line| line.parse().unwrap()).collect();
+
+        Rules::new(&rules).unwrap()
+    }
+
+    fn example() -> (Pots, Rules) {
+        ("#..#.#..##......###...###".parse().unwrap(), rules(EXAMPLE_RULES))
+    }
+
+    #[test]
+    fn test_parse() {
+        let pots: Pots = "..#.#..".parse().unwrap();
+
+        assert_eq!(pots.first, 2);
+        assert_eq!(pots.to_string(), "#.#");
+        assert_eq!(pots.sum(), 2 + 4);
+
+        let empty: Pots = "....".parse().unwrap();
+        assert_eq!((empty.sum(), empty.to_string()), (0, "".to_string()));
+
+        let err = "#..x".parse::<Pots>().unwrap_err();
+        assert_eq!(format!("{}", err), "Error parsing Pots from string: '#..x'");
+    }
+
+    #[test]
+    fn test_generations() {
+        let (mut pots, rules) = example();
+
+        pots = pots.next(&rules);
+        assert_eq!((pots.first, pots.to_string()), (0, "#...#....#.....#..#..#..#".to_string()));
+
+        pots = pots.next(&rules);
+        assert_eq!((pots.first, pots.to_string()), (0, "##..##...##....#..#..#..##".to_string()));
+
+        for _ in 2..20 {
+            pots = pots.next(&rules);
+        }
+
+        assert_eq!((pots.first, pots.to_string()),
+                   (-2, "#....##....#####...#######....#.#..##".to_string()));
+        assert_eq!(pots.sum(), 325);
+    }
+
+    #[test]
+    fn test_sum_after() {
+        let (pots, rules) = example();
+
+        assert_eq!(pots.sum_after(&rules, 0).unwrap(), pots.sum());
+        assert_eq!(pots.sum_after(&rules, 20).unwrap(), 325);
+
+        // simulating all the way agrees with extrapolating
+        let simulated = (0..500).fold(pots.clone(), |pots, _| pots.next(&rules));
+        assert_eq!(pots.sum_after(&rules, 500).unwrap(), simulated.sum());
+
+        assert_eq!(pots.sum_after(&rules, 50_000_000_000).unwrap(), 999_999_999_374);
+        assert_eq!(pots.sum_after(&rules, u64::MAX).unwrap(), 20 * i128::from(u64::MAX) - 626);
+    }
+
+    #[test]
+    fn test_dying_plants() {
+        // a lone plant dies straight away
+        let pots: Pots = "#".parse().unwrap();
+        assert_eq!(pots.sum_after(&rules(&["..#.. => ."]), 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_no_steady_state() {
+        // a plant where exactly one of the neighbours has one, spreading
+        // out into ever larger triangles
+        let pots: Pots = "#".parse().unwrap();
+        let rules: Vec<_> = (0..32)
+            .map(|pattern| Rule { pattern, plant: (pattern >> 3 & 1) != (pattern >> 1 & 1) })
+            .collect();
+        let spreading = Rules::new(&rules).unwrap();
+
+        assert_eq!(pots.sum_after(&spreading, 10).unwrap(), 0);
+
+        let err = pots.sum_after(&spreading, 10_001).unwrap_err();
+        assert_eq!(format!("{}", err), "Plants don't settle into a steady pattern within 10000 generations");
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::str::FromStr;
+
+use common::Fail;
+
+
+pub const PLANT: char = '#';
+pub const EMPTY: char = '.';
+
+// pots a rule looks at: two on each side of the middle one
+pub const WINDOW: usize = 5;
+
+// one entry per arrangement of the pots in the window
+const TABLE_SIZE: usize = 1 << WINDOW;
+
+
+/// Line of the notes, e.g. "..#.# => #": whether the middle pot has a
+/// plant in the next generation, for these five pots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    // pots as bits, the leftmost one the highest
+    pub pattern: usize,
+    pub plant: bool,
+}
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Error parsing Rule from string: '{}'", string)]
+pub struct ParseRuleError {
+    string: String,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum RulesError {
+    #[fail(display = "Conflicting rules for {}", pattern)]
+    Conflicting {
+        pattern: String,
+    },
+
+    #[fail(display = "Plants would grow in infinitely many empty pots")]
+    InfiniteGrowth,
+}
+
+
+/// What grows in a pot, looked up by the pattern of the five pots
+/// around it.  Patterns without a rule grow nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    table: [bool; TABLE_SIZE],
+}
+
+
+/// Pot as a bit, 1 for a plant.
+pub fn parse_pot(ch: char) -> Option<bool> {
+    match ch {
+        PLANT => Some(true),
+        EMPTY => Some(false),
+        _ => None,
+    }
+}
+
+
+pub fn pot_char(plant: bool) -> char {
+    if plant { PLANT } else { EMPTY }
+}
+
+
+fn pattern_string(pattern: usize) -> String {
+    (0..WINDOW).rev().map(|bit| pot_char(pattern >> bit & 1 == 1)).collect()
+}
+
+
+impl Rules {
+    /// Lookup table of the rules.  Empty pots can't grow plants when
+    /// surrounded by empty pots, or infinitely many would.
+    pub fn new(rules: &[Rule]) -> Result<Rules, RulesError> {
+        let mut table = [false; TABLE_SIZE];
+        let mut given = [false; TABLE_SIZE];
+
+        for rule in rules {
+            if given[rule.pattern] && table[rule.pattern] != rule.plant {
+                return Err(RulesError::Conflicting { pattern: pattern_string(rule.pattern) });
+            }
+
+            table[rule.pattern] = rule.plant;
+            given[rule.pattern] = true;
+        }
+
+        if table[0] {
+            return Err(RulesError::InfiniteGrowth);
+        }
+
+        Ok(Rules { table })
+    }
+
+    /// Whether the middle pot of the pattern has a plant next.
+    pub fn grows(&self, pattern: usize) -> bool {
+        self.table[pattern]
+    }
+}
+
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Rule, ParseRuleError> {
+        let malformed = || ParseRuleError { string: s.into() };
+
+        let mut sides = s.trim().splitn(2, " => ");
+        let (pots, result) = match (sides.next(), sides.next()) {
+            (Some(pots), Some(result)) => (pots, result),
+            _ => return Err(malformed()),
+        };
+
+        if pots.chars().count() != WINDOW {
+            return Err(malformed());
+        }
+
+        let pattern = pots
+            .chars()
+            .try_fold(0, |pattern, pot| Some(pattern << 1 | parse_pot(pot)? as usize))
+            .ok_or_else(malformed)?;
+
+        let mut result = result.chars();
+
+        let plant = match (result.next().and_then(parse_pot), result.next()) {
+            (Some(plant), None) => plant,
+            _ => return Err(malformed()),
+        };
+
+        Ok(Rule { pattern, plant })
+    }
+}
+
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} => {}", pattern_string(self.pattern), pot_char(self.plant))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Rule, Rules};
+
+    fn rules(lines: &[&str]) -> Vec<Rule> {
+        lines.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("..#.# => #".parse::<Rule>().unwrap(), Rule { pattern: 0b00101, plant: true });
+        assert_eq!("##... => .".parse::<Rule>().unwrap(), Rule { pattern: 0b11000, plant: false });
+        assert_eq!("#.#.# => .".parse::<Rule>().unwrap().to_string(), "#.#.# => .");
+
+        for line in &["..#. => #", "..#.#. => #", "..#.# => ", "..#.# => ##", "..x.# => #", "..#.# -> #"] {
+            let err = line.parse::<Rule>().unwrap_err();
+            assert_eq!(format!("{}", err), format!("Error parsing Rule from string: '{}'", line));
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::new(&rules(&["..#.. => #", "##.## => .", "##.## => .", "#...# => #"])).unwrap();
+
+        assert!(rules.grows(0b00100));
+        assert!(rules.grows(0b10001));
+        assert!(!rules.grows(0b11011));
+
+        // no rule, no plant
+        assert!(!rules.grows(0b11111));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let err = Rules::new(&rules(&["##.## => #", "..#.. => #", "##.## => ."])).unwrap_err();
+        assert_eq!(format!("{}", err), "Conflicting rules for ##.##");
+
+        let err = Rules::new(&rules(&["..... => #"])).unwrap_err();
+        assert_eq!(format!("{}", err), "Plants would grow in infinitely many empty pots");
+
+        assert!(Rules::new(&rules(&["..... => ."])).is_ok());
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    const EXAMPLE: &str = "initial state: #..#.#..##......###...###\n\
+                           \n\
+                           ...## => #\n\
+                           ..#.. => #\n\
+                           .#... => #\n\
+                           .#.#. => #\n\
+                           .#.## => #\n\
+                           .##.. => #\n\
+                           .#### => #\n\
+                           #.#.# => #\n\
+                           #.### => #\n\
+                           ##.#. => #\n\
+                           ##.## => #\n\
+                           ###.. => #\n\
+                           ###.# => #\n\
+                           ####. => #\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("Sum of pots with plants after 20 generations: 325\n\
+                     Sum of pots with plants after 50000000000 generations: 999999999374\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(EXAMPLE.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("Sum of pots with plants after 20 generations: 325\n\
+                     Sum of pots with plants after 50000000000 generations: 999999999374\n");
+    }
+
+    #[test]
+    fn test_generations() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.args(["--generations", "0"]);
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(EXAMPLE);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Sum of pots with plants after 0 generations: 145\n");
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("initial state: #..#\n\n..#.. => #\n..#. => #\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Error parsing Rule from string: '..#. => #'. Aborting.\n");
+    }
+
+    #[test]
+    fn test_infinite_growth() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("initial state: #..#\n\n..... => #\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Plants would grow in infinitely many empty pots. Aborting.\n");
+    }
+}
@@ -0,0 +1,158 @@
+/// Cell of the map as (x, y), with (0, 0) the top left one.
+pub type Position = (usize, usize);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+
+/// Which way a cart goes at its next intersection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cart {
+    pub position: Position,
+    pub direction: Direction,
+    pub next_turn: Turn,
+}
+
+
+impl Direction {
+    pub fn from_char(ch: char) -> Option<Direction> {
+        match ch {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        self == Direction::Up || self == Direction::Down
+    }
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// The next cell this way, `None` past the top or left edge.
+    pub fn step(self, (x, y): Position) -> Option<Position> {
+        match self {
+            Direction::Up => Some((x, y.checked_sub(1)?)),
+            Direction::Right => Some((x + 1, y)),
+            Direction::Down => Some((x, y + 1)),
+            Direction::Left => Some((x.checked_sub(1)?, y)),
+        }
+    }
+}
+
+
+impl Turn {
+    /// Carts turn left, go straight, then turn right, over and over.
+    pub fn next(self) -> Turn {
+        match self {
+            Turn::Left => Turn::Straight,
+            Turn::Straight => Turn::Right,
+            Turn::Right => Turn::Left,
+        }
+    }
+
+    pub fn apply(self, direction: Direction) -> Direction {
+        match self {
+            Turn::Left => direction.turn_left(),
+            Turn::Straight => direction,
+            Turn::Right => direction.turn_right(),
+        }
+    }
+}
+
+
+impl Cart {
+    /// Cart about to turn left at its first intersection.
+    pub fn new(position: Position, direction: Direction) -> Cart {
+        Cart { position, direction, next_turn: Turn::Left }
+    }
+
+    /// Takes the intersection the cart is on.
+    pub fn cross(&mut self) {
+        self.direction = self.next_turn.apply(self.direction);
+        self.next_turn = self.next_turn.next();
+    }
+
+    /// Order the carts move in, top row first, then left to right.
+    pub fn reading_order(&self) -> (usize, usize) {
+        let (x, y) = self.position;
+
+        (y, x)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Cart, Direction, Turn};
+
+    #[test]
+    fn test_directions() {
+        for ch in "^>v<".chars() {
+            assert_eq!(Direction::from_char(ch).map(Direction::to_char), Some(ch));
+        }
+
+        assert_eq!(Direction::from_char('x'), None);
+
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+        assert_eq!(Direction::Up.step((3, 0)), None);
+        assert_eq!(Direction::Left.step((3, 0)), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_intersections() {
+        let mut cart = Cart::new((0, 0), Direction::Right);
+
+        let directions: Vec<_> = (0..4)
+            .map(|_| {
+                cart.cross();
+                cart.direction
+            })
+            .collect();
+
+        assert_eq!(directions, vec![Direction::Up, Direction::Up, Direction::Right, Direction::Up]);
+        assert_eq!(cart.next_turn, Turn::Straight);
+    }
+}
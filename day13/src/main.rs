@@ -0,0 +1,70 @@
+use std::env;
+
+use common::{get_input_text, parse_options, Error};
+
+mod cart;
+mod mine;
+mod track;
+
+use crate::mine::Mine;
+
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("Error: {}. Aborting.", err);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (args, options) = parse_options(args, &[], &["replay"])?;
+
+    let mut mine: Mine = get_input_text(&args)?.parse()?;
+
+    // every frame of the carts moving, before the answers
+    let replay = options.flag("replay");
+
+    if replay {
+        print_frame(&mine, &[]);
+    }
+
+    let outcome = mine.run(|mine, crashes| {
+        if replay {
+            print_frame(mine, crashes);
+        }
+    })?;
+
+    // part 1
+
+    match outcome.first_crash {
+        Some(crash) => {
+            let (x, y) = crash.position;
+            println!("First crash: {},{} (tick {})", x, y, crash.tick);
+        },
+        None => println!("No crashes"),
+    }
+
+    // part 2
+
+    match outcome.last_cart {
+        Some(cart) => {
+            let (x, y) = cart.position;
+            println!("Last cart: {},{} (tick {})", x, y, outcome.ticks);
+        },
+        None => println!("No carts left"),
+    }
+
+    Ok(())
+}
+
+
+fn print_frame(mine: &Mine, crashes: &[cart::Position]) {
+    println!("Tick {}:", mine.ticks());
+    print!("{}", mine.render(crashes));
+    println!();
+}
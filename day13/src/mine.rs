@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use common::grid::Grid;
+use common::Fail;
+
+use crate::cart::{Cart, Direction, Position};
+use crate::track::Track;
+
+
+pub const CRASH: char = 'X';
+
+// carts still going after this many ticks are taken to go on forever
+const MAX_TICKS: u64 = 1_000_000;
+
+
+#[derive(Debug, Fail)]
+#[fail(display = "Invalid character '{}' on the map at {},{}", ch, x, y)]
+pub struct ParseMineError {
+    ch: char,
+    x: usize,
+    y: usize,
+}
+
+
+#[derive(Debug, Fail)]
+pub enum MineError {
+    #[fail(display = "Cart at {},{} runs off the track", x, y)]
+    OffTrack {
+        x: usize,
+        y: usize,
+    },
+
+    #[fail(display = "Carts still running after {} ticks", ticks)]
+    NeverEnds {
+        ticks: u64,
+    },
+}
+
+
+/// Where and when two carts crashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crash {
+    pub position: Position,
+    pub tick: u64,
+}
+
+
+/// How the carts end up, once at most one is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub first_crash: Option<Crash>,
+    // `None` if every cart crashed
+    pub last_cart: Option<Cart>,
+    pub ticks: u64,
+}
+
+
+/// Map of the tracks, with the carts on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mine {
+    tracks: Grid<Track>,
+    carts: Vec<Cart>,
+    ticks: u64,
+}
+
+
+impl Mine {
+    /// Ticks simulated so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Moves every cart one cell, in reading order of where they are at
+    /// the start of the tick.  Two carts in the same cell crash and are
+    /// removed right away: a cart hit before its move doesn't move.
+    /// Returns where crashes happened, in order.
+    pub fn tick(&mut self) -> Result<Vec<Position>, MineError> {
+        let tracks = &self.tracks;
+
+        self.carts.sort_by_key(Cart::reading_order);
+
+        let mut occupied: HashMap<Position, usize> = self.carts
+            .iter()
+            .enumerate()
+            .map(|(index, cart)| (cart.position, index))
+            .collect();
+
+        let mut crashed = vec![false; self.carts.len()];
+        let mut crashes = vec![];
+
+        for index in 0..self.carts.len() {
+            if crashed[index] {
+                continue;
+            }
+
+            let cart = &mut self.carts[index];
+            let (x, y) = cart.position;
+
+            occupied.remove(&cart.position);
+
+            let position = cart.direction
+                .step(cart.position)
+                .filter(|&(x, y)| tracks.contains(x, y))
+                .ok_or(MineError::OffTrack { x, y })?;
+
+            cart.position = position;
+
+            if !tracks[position].guide(cart) {
+                return Err(MineError::OffTrack { x, y });
+            }
+
+            match occupied.remove(&position) {
+                Some(other) => {
+                    crashed[index] = true;
+                    crashed[other] = true;
+                    crashes.push(position);
+                },
+                None => {
+                    occupied.insert(position, index);
+                },
+            }
+        }
+
+        let mut crashed = crashed.into_iter();
+        self.carts.retain(|_| !crashed.next().unwrap_or(false));
+
+        self.ticks += 1;
+
+        Ok(crashes)
+    }
+
+    /// Ticks until at most one cart is left, calling `on_tick` after
+    /// each tick with the crashes in it.
+    pub fn run(&mut self, mut on_tick: impl FnMut(&Mine, &[Position])) -> Result<Outcome, MineError> {
+        let mut first_crash = None;
+
+        while self.carts.len() > 1 {
+            if self.ticks >= MAX_TICKS {
+                return Err(MineError::NeverEnds { ticks: self.ticks });
+            }
+
+            let crashes = self.tick()?;
+
+            if first_crash.is_none() {
+                first_crash = crashes.first().map(|&position| Crash { position, tick: self.ticks });
+            }
+
+            on_tick(self, &crashes);
+        }
+
+        Ok(Outcome { first_crash, last_cart: self.carts.first().cloned(), ticks: self.ticks })
+    }
+
+    /// The map with the carts on it, and the crashes marked.
+    pub fn render(&self, crashes: &[Position]) -> Grid<char> {
+        let mut map = self.tracks.map(|track| track.to_char());
+
+        for cart in &self.carts {
+            map[cart.position] = cart.direction.to_char();
+        }
+
+        for &position in crashes {
+            map[position] = CRASH;
+        }
+
+        map
+    }
+}
+
+
+/// Map with a row per line, shorter lines padded with empty ground.
+impl FromStr for Mine {
+    type Err = ParseMineError;
+
+    fn from_str(s: &str) -> Result<Mine, ParseMineError> {
+        let lines: Vec<&str> = s.trim_end_matches('\n').lines().collect();
+
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut rows = Vec::with_capacity(lines.len());
+        let mut carts = vec![];
+
+        for (y, line) in lines.iter().enumerate() {
+            let mut row = Vec::with_capacity(width);
+
+            for (x, ch) in line.chars().enumerate() {
+                let track = match Direction::from_char(ch) {
+                    Some(direction) => {
+                        carts.push(Cart::new((x, y), direction));
+                        Track::under(direction)
+                    },
+                    None => Track::from_char(ch).ok_or(ParseMineError { ch, x, y })?,
+                };
+
+                row.push(track);
+            }
+
+            row.resize(width, Track::Empty);
+            rows.push(row);
+        }
+
+        let tracks = Grid::from_fn(width, rows.len(), |x, y| rows[y][x]);
+
+        Ok(Mine { tracks, carts, ticks: 0 })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::cart::{Cart, Direction};
+
+    use super::{Crash, Mine};
+
+    const CRASHING: &str = r"/->-\
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/
+";
+
+    const LAST_CART: &str = r"/>-<\
+|   |
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/
+";
+
+    fn frame(mine: &Mine, crashes: &[(usize, usize)]) -> String {
+        mine.render(crashes)
+            .rows()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let mine: Mine = CRASHING.parse().unwrap();
+
+        assert_eq!(mine.tracks.size(), (13, 6));
+        assert_eq!(mine.carts, vec![
+            Cart::new((2, 0), Direction::Right),
+            Cart::new((9, 3), Direction::Down),
+        ]);
+        assert_eq!(frame(&mine, &[]), CRASHING);
+
+        let err = "/-\\\n|.|\n".parse::<Mine>().unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid character '.' on the map at 1,1");
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut mine: Mine = CRASHING.parse().unwrap();
+
+        assert!(mine.tick().unwrap().is_empty());
+        assert_eq!(frame(&mine, &[]), r"/-->\
+|   |  /----\
+| /-+--+-\  |
+| | |  | |  |
+\-+-/  \->--/
+  \------/
+");
+
+        for _ in 1..13 {
+            assert!(mine.tick().unwrap().is_empty());
+        }
+
+        assert_eq!(mine.tick().unwrap(), vec![(7, 3)]);
+        assert_eq!(mine.ticks(), 14);
+        assert!(mine.carts.is_empty());
+    }
+
+    #[test]
+    fn test_first_crash() {
+        let mut mine: Mine = CRASHING.parse().unwrap();
+        let outcome = mine.run(|_, _| ()).unwrap();
+
+        assert_eq!(outcome.first_crash, Some(Crash { position: (7, 3), tick: 14 }));
+        assert_eq!(outcome.last_cart, None);
+    }
+
+    #[test]
+    fn test_last_cart() {
+        let mut mine: Mine = LAST_CART.parse().unwrap();
+        let mut frames = vec![];
+
+        let outcome = mine.run(|mine, crashes| frames.push(frame(mine, crashes))).unwrap();
+
+        assert_eq!(outcome.first_crash, Some(Crash { position: (2, 0), tick: 1 }));
+        assert_eq!(outcome.ticks, 3);
+        assert_eq!(outcome.last_cart.map(|cart| cart.position), Some((6, 4)));
+
+        // three crashes in the first tick, the carts hit before their
+        // move not moving
+        assert_eq!(frames[0], r"/-X-\
+|   |
+| v-+-\
+| | | |
+\-X-/ X
+  |   |
+  ^---^
+");
+        assert_eq!(frames.len(), 3);
+    }
+
+    #[test]
+    fn test_reading_order() {
+        // the left cart moves first and crashes into the right one,
+        // which never gets to move away
+        let mut mine: Mine = "->>-".parse().unwrap();
+
+        assert_eq!(mine.tick().unwrap(), vec![(2, 0)]);
+        assert!(mine.carts.is_empty());
+
+        // the left cart moves first, out of the way of the right one
+        let mut mine: Mine = "-<<-".parse().unwrap();
+
+        assert!(mine.tick().unwrap().is_empty());
+        assert_eq!(mine.carts.len(), 2);
+
+        // same for the top cart
+        let mut mine: Mine = "|\n^\n^\n|\n".parse().unwrap();
+
+        assert!(mine.tick().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_off_track() {
+        let mut mine: Mine = "-->\n".parse().unwrap();
+        let err = mine.tick().unwrap_err();
+        assert_eq!(format!("{}", err), "Cart at 2,0 runs off the track");
+
+        let mut mine: Mine = "/>|\n".parse().unwrap();
+        let err = mine.tick().unwrap_err();
+        assert_eq!(format!("{}", err), "Cart at 1,0 runs off the track");
+    }
+
+    #[test]
+    fn test_single_cart() {
+        let mut mine: Mine = "/>\\\n\\-/\n".parse().unwrap();
+        let outcome = mine.run(|_, _| ()).unwrap();
+
+        assert_eq!(outcome.first_crash, None);
+        assert_eq!(outcome.ticks, 0);
+        assert_eq!(outcome.last_cart, Some(Cart::new((1, 0), Direction::Right)));
+    }
+}
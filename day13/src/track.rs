@@ -0,0 +1,94 @@
+use crate::cart::{Cart, Direction};
+
+
+/// Cell of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Track {
+    Empty,
+    Horizontal,
+    Vertical,
+    // '/'
+    CurveUp,
+    // '\'
+    CurveDown,
+    Intersection,
+}
+
+
+impl Track {
+    pub fn from_char(ch: char) -> Option<Track> {
+        match ch {
+            ' ' => Some(Track::Empty),
+            '-' => Some(Track::Horizontal),
+            '|' => Some(Track::Vertical),
+            '/' => Some(Track::CurveUp),
+            '\\' => Some(Track::CurveDown),
+            '+' => Some(Track::Intersection),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Track::Empty => ' ',
+            Track::Horizontal => '-',
+            Track::Vertical => '|',
+            Track::CurveUp => '/',
+            Track::CurveDown => '\\',
+            Track::Intersection => '+',
+        }
+    }
+
+    /// Track under a cart on the initial map: always straight.
+    pub fn under(direction: Direction) -> Track {
+        if direction.is_vertical() { Track::Vertical } else { Track::Horizontal }
+    }
+
+    /// Points the cart which just moved onto this track the way the
+    /// track goes.  `false` if the cart can't be on it, e.g. empty
+    /// ground or going across a straight track.
+    pub fn guide(self, cart: &mut Cart) -> bool {
+        let vertical = cart.direction.is_vertical();
+
+        match self {
+            Track::Empty => return false,
+            Track::Horizontal => return !vertical,
+            Track::Vertical => return vertical,
+            Track::CurveUp if vertical => cart.direction = cart.direction.turn_right(),
+            Track::CurveUp => cart.direction = cart.direction.turn_left(),
+            Track::CurveDown if vertical => cart.direction = cart.direction.turn_left(),
+            Track::CurveDown => cart.direction = cart.direction.turn_right(),
+            Track::Intersection => cart.cross(),
+        }
+
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::cart::{Cart, Direction};
+
+    use super::Track;
+
+    fn guided(track: char, direction: Direction) -> Option<Direction> {
+        let mut cart = Cart::new((0, 0), direction);
+
+        if Track::from_char(track).unwrap().guide(&mut cart) { Some(cart.direction) } else { None }
+    }
+
+    #[test]
+    fn test_guide() {
+        assert_eq!(guided('/', Direction::Up), Some(Direction::Right));
+        assert_eq!(guided('/', Direction::Left), Some(Direction::Down));
+        assert_eq!(guided('\\', Direction::Up), Some(Direction::Left));
+        assert_eq!(guided('\\', Direction::Right), Some(Direction::Down));
+        assert_eq!(guided('+', Direction::Down), Some(Direction::Right));
+        assert_eq!(guided('-', Direction::Left), Some(Direction::Left));
+
+        assert_eq!(guided('-', Direction::Up), None);
+        assert_eq!(guided('|', Direction::Right), None);
+        assert_eq!(guided(' ', Direction::Right), None);
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod cli {
+    use std::io::Write;
+    use std::process::Command;
+
+    use assert_cmd::prelude::*;
+
+    const CRASHING: &str = "/->-\\        \n\
+                            |   |  /----\\\n\
+                            | /-+--+-\\  |\n\
+                            | | |  | v  |\n\
+                            \\-+-/  \\-+--/\n\
+                            \x20 \\------/   \n";
+
+    const LAST_CART: &str = "/>-<\\  \n\
+                             |   |  \n\
+                             | /<+-\\\n\
+                             | | | v\n\
+                             \\>+</ |\n\
+                             \x20 |   ^\n\
+                             \x20 \\<->/\n";
+
+    #[test]
+    fn test_run_stdin() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer(LAST_CART);
+
+        assert_cmd
+            .assert()
+            .success()
+            .stderr("Reading input from stdin.\n")
+            .stdout("First crash: 2,0 (tick 1)\n\
+                     Last cart: 6,4 (tick 3)\n");
+    }
+
+    #[test]
+    fn test_run_input_file() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(CRASHING.as_bytes()).unwrap();
+
+        cmd.arg(tmp_file.path());
+
+        cmd
+            .assert()
+            .success()
+            .stdout("First crash: 7,3 (tick 14)\n\
+                     No carts left\n");
+    }
+
+    #[test]
+    fn test_single_cart() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("/->\\\n\\--/\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("No crashes\n\
+                     Last cart: 2,0 (tick 0)\n");
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut cmd = Command::main_binary().unwrap();
+        cmd.arg("--replay");
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("/>-<-<\\\n\\-----/\n");
+
+        assert_cmd
+            .assert()
+            .success()
+            .stdout("Tick 0:\n\
+                     />-<-<\\\n\
+                     \\-----/\n\
+                     \n\
+                     Tick 1:\n\
+                     /-X-<-\\\n\
+                     \\-----/\n\
+                     \n\
+                     First crash: 2,0 (tick 1)\n\
+                     Last cart: 4,0 (tick 1)\n");
+    }
+
+    #[test]
+    fn test_invalid_map() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("/->-\\\n|   *\n\\---/\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Invalid character '*' on the map at 4,1. Aborting.\n");
+    }
+
+    #[test]
+    fn test_off_track() {
+        let mut cmd = Command::main_binary().unwrap();
+
+        let mut stdin_cmd = cmd.with_stdin();
+        let mut assert_cmd = stdin_cmd.buffer("/->\n| ^\n\\-/\n");
+
+        assert_cmd
+            .assert()
+            .failure()
+            .stderr("Reading input from stdin.\n\
+                     Error: Cart at 2,0 runs off the track. Aborting.\n");
+    }
+}